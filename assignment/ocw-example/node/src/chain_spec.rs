@@ -1,6 +1,6 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, OcwDemoConfig, Signature,
+	SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -150,5 +150,20 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		},
+		ocw_demo: OcwDemoConfig {
+			// Fetch the DOT price from coincap by default, more sources can be added by sudo.
+			price_sources: vec![(
+				b"DOT".to_vec(),
+				b"https://api.coincap.io/v2/assets/polkadot".to_vec(),
+				b"/data/priceUsd".to_vec(),
+			)],
+			// Alice is the key the node inserts for offchain workers.
+			reporters: vec![get_account_id_from_seed::<sr25519::Public>("Alice")],
+			// Attest the github organization info, the document is served by `ocw_attestedDocument`.
+			attestation_sources: vec![(
+				b"github-org".to_vec(),
+				b"https://api.github.com/orgs/substrate-developer-hub".to_vec(),
+			)],
+		},
 	}
 }
@@ -0,0 +1,168 @@
+//! RPC methods of pallet-ocw, to inspect the offchain worker of the node, the price
+//! statistics and the attested documents.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_ocw::TaskStatus;
+use pallet_ocw_runtime_api::OcwApi as OcwRuntimeApi;
+use parity_scale_codec::{Codec, Decode};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::blake2_256, offchain::OffchainStorage, Bytes, H256};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+mod price;
+
+pub use price::{Price, PriceApi, WindowStats};
+
+/// Schedule and last runs of an offchain task.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerStatus<BlockNumber> {
+	/// Name of the task, e.g. `price-info`.
+	pub task: String,
+	pub interval: BlockNumber,
+	pub offset: BlockNumber,
+	pub enabled: bool,
+	/// The block of the last run on this node, successful or not.
+	pub last_run: Option<BlockNumber>,
+	/// The block and error of the last failed run on this node.
+	pub last_error: Option<(BlockNumber, String)>,
+}
+
+/// The latest attested document of a source, along with its on-chain attestation.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestedDocument<BlockNumber> {
+	pub source: String,
+	/// `blake2_256` hash of the document, as attested on-chain.
+	pub hash: H256,
+	/// The block the document was fetched in.
+	pub block_number: BlockNumber,
+	/// The document, `None` when this node has not indexed it.
+	pub document: Option<Bytes>,
+	/// Whether the hash of `document` matches the attested one.
+	pub verified: bool,
+}
+
+#[rpc]
+pub trait OcwApi<BlockHash, BlockNumber> {
+	/// The status of every offchain task, the schedules are read at block `at`.
+	#[rpc(name = "ocw_workerStatus")]
+	fn worker_status(&self, at: Option<BlockHash>) -> Result<Vec<WorkerStatus<BlockNumber>>>;
+
+	/// The latest attested document of `source`, e.g. `github-org`, as of block `at`. The node
+	/// only has the documents when it runs with `--enable-offchain-indexing true`.
+	#[rpc(name = "ocw_attestedDocument")]
+	fn attested_document(
+		&self,
+		source: String,
+		at: Option<BlockHash>,
+	) -> Result<Option<AttestedDocument<BlockNumber>>>;
+}
+
+/// Error code of a failing runtime call.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Implements `OcwApi` with the client of the node and its offchain storage, which is `None`
+/// when the node runs without offchain workers.
+pub struct Ocw<C, Block, S> {
+	client: Arc<C>,
+	storage: Option<S>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block, S> Ocw<C, Block, S> {
+	pub fn new(client: Arc<C>, storage: Option<S>) -> Self {
+		Self { client, storage, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, S, BlockNumber> OcwApi<<Block as BlockT>::Hash, BlockNumber> for Ocw<C, Block, S>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: OcwRuntimeApi<Block, BlockNumber>,
+	S: OffchainStorage + 'static,
+	BlockNumber: Codec + Send + Sync + 'static,
+{
+	fn worker_status(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<WorkerStatus<BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let schedules = api.task_schedules(&at).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to query the task schedules.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		Ok(schedules
+			.into_iter()
+			.map(|(task, schedule)| {
+				// the pallet writes the status in the persistent offchain storage
+				let status = self
+					.storage
+					.as_ref()
+					.and_then(|storage| {
+						storage.get(sp_offchain::STORAGE_PREFIX, &task.status_key())
+					})
+					.and_then(|raw| TaskStatus::<BlockNumber>::decode(&mut &raw[..]).ok())
+					.unwrap_or_default();
+				WorkerStatus {
+					task: String::from_utf8_lossy(task.name()).into_owned(),
+					interval: schedule.interval,
+					offset: schedule.offset,
+					enabled: schedule.enabled,
+					last_run: status.last_run,
+					last_error: status.last_error.map(|(block, error)| {
+						(block, String::from_utf8_lossy(&error).into_owned())
+					}),
+				}
+			})
+			.collect())
+	}
+
+	fn attested_document(
+		&self,
+		source: String,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<AttestedDocument<BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let attestation =
+			api.attestation(&at, source.clone().into_bytes()).map_err(|e| RpcError {
+				code: ErrorCode::ServerError(RUNTIME_ERROR),
+				message: "Unable to query the attestation.".into(),
+				data: Some(format!("{:?}", e).into()),
+			})?;
+		let attestation = match attestation {
+			Some(attestation) => attestation,
+			None => return Ok(None),
+		};
+
+		// the pallet indexes the document in the persistent offchain storage
+		let key = pallet_ocw::document_key(source.as_bytes(), &attestation.block_number);
+		let document = self
+			.storage
+			.as_ref()
+			.and_then(|storage| storage.get(sp_offchain::STORAGE_PREFIX, &key));
+		let verified = document
+			.as_ref()
+			.map_or(false, |document| H256(blake2_256(document)) == attestation.hash);
+
+		Ok(Some(AttestedDocument {
+			source,
+			hash: attestation.hash,
+			block_number: attestation.block_number,
+			document: document.map(Bytes),
+			verified,
+		}))
+	}
+}
@@ -0,0 +1,111 @@
+//! RPC methods reading the price statistics, in their own module as `#[rpc]` generates a
+//! `gen_client` module per trait.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_ocw_runtime_api::PriceApi as PriceRuntimeApi;
+use parity_scale_codec::Codec;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT, FixedPointNumber, FixedU128};
+
+use super::RUNTIME_ERROR;
+
+/// Statistics of the prices of an asset over a window of blocks, the prices are decimal strings.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowStats<BlockNumber> {
+	/// Length of the window in blocks.
+	pub window: BlockNumber,
+	pub twap: String,
+	pub min: String,
+	pub max: String,
+	pub volatility: String,
+	/// The block of the last aggregated price, where the window ends.
+	pub block_number: BlockNumber,
+}
+
+#[rpc]
+pub trait PriceApi<BlockHash, BlockNumber> {
+	/// The statistics of the prices of `symbol`, e.g. `DOT`, over each configured window.
+	#[rpc(name = "ocw_priceStats")]
+	fn price_stats(
+		&self,
+		symbol: String,
+		at: Option<BlockHash>,
+	) -> Result<Vec<WindowStats<BlockNumber>>>;
+}
+
+/// Implements `PriceApi` with the client of the node.
+pub struct Price<C, Block> {
+	client: Arc<C>,
+	_marker: PhantomData<Block>,
+}
+
+impl<C, Block> Price<C, Block> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, BlockNumber> PriceApi<<Block as BlockT>::Hash, BlockNumber> for Price<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: PriceRuntimeApi<Block, BlockNumber>,
+	BlockNumber: Codec + Send + Sync + 'static,
+{
+	fn price_stats(
+		&self,
+		symbol: String,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<WindowStats<BlockNumber>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let stats = api.price_stats(&at, symbol.into_bytes()).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to query the price statistics.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})?;
+
+		Ok(stats
+			.into_iter()
+			.map(|(window, stats)| WindowStats {
+				window,
+				twap: decimal(stats.twap),
+				min: decimal(stats.min),
+				max: decimal(stats.max),
+				volatility: decimal(stats.volatility),
+				block_number: stats.block_number,
+			})
+			.collect())
+	}
+}
+
+/// `value` in decimal notation without trailing zeros, e.g. `1234.5`.
+fn decimal(value: FixedU128) -> String {
+	let (integer, fraction) =
+		(value.into_inner() / FixedU128::DIV, value.into_inner() % FixedU128::DIV);
+	if fraction == 0 {
+		return integer.to_string();
+	}
+	let fraction = format!("{:018}", fraction);
+	format!("{}.{}", integer, fraction.trim_end_matches('0'))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decimal_trims_trailing_zeros() {
+		assert_eq!(decimal(FixedU128::saturating_from_integer(12)), "12");
+		assert_eq!(decimal(FixedU128::saturating_from_rational(12345, 10)), "1234.5");
+		assert_eq!(decimal(FixedU128::from_inner(1)), "0.000000000000000001");
+		assert_eq!(decimal(FixedU128::from_inner(0)), "0");
+	}
+}
@@ -0,0 +1,31 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime APIs of pallet-ocw, to read the aggregated prices, their statistics, the offchain
+//! task schedules and the document attestations without a transaction.
+
+use pallet_ocw::{Attestation, OffchainTask, PriceStats, TaskSchedule};
+use parity_scale_codec::Codec;
+use sp_arithmetic::FixedU128;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait OcwApi<BlockNumber> where
+		BlockNumber: Codec,
+	{
+		/// The median price of the last finalized round of `symbol`, as
+		/// `(price, round, block_number)`.
+		fn aggregated_price(symbol: Vec<u8>) -> Option<(FixedU128, u32, BlockNumber)>;
+		/// The schedule of every offchain task.
+		fn task_schedules() -> Vec<(OffchainTask, TaskSchedule<BlockNumber>)>;
+		/// The latest attestation of the document of `source`.
+		fn attestation(source: Vec<u8>) -> Option<Attestation<BlockNumber>>;
+	}
+
+	pub trait PriceApi<BlockNumber> where
+		BlockNumber: Codec,
+	{
+		/// The statistics of `symbol` over each configured window, as `(window, stats)`. Empty
+		/// until a price of `symbol` is aggregated.
+		fn price_stats(symbol: Vec<u8>) -> Vec<(BlockNumber, PriceStats<BlockNumber>)>;
+	}
+}
@@ -2,12 +2,20 @@
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+pub mod migrations;
+
 #[frame_support::pallet]
 pub mod pallet {
 	//! A demonstration of an offchain worker that sends onchain callbacks
-	use core::{convert::TryInto, fmt};
+	use core::convert::TryInto;
 	use parity_scale_codec::{Decode, Encode};
-	use frame_support::pallet_prelude::*;
+	use frame_support::{pallet_prelude::*, traits::StorageVersion};
 	use frame_system::{
 		pallet_prelude::*,
 		offchain::{
@@ -15,25 +23,30 @@ pub mod pallet {
 			SignedPayload, Signer, SigningTypes, SubmitTransaction,
 		},
 	};
-	use sp_core::{crypto::KeyTypeId};
-	use sp_arithmetic::per_things::Permill;
+	use sp_core::{crypto::KeyTypeId, H256, U256};
+	use sp_arithmetic::{
+		per_things::{PerThing, Permill},
+		FixedPointNumber, FixedU128,
+	};
 	use sp_runtime::{
 		offchain as rt_offchain,
 		traits::{
-			BlockNumberProvider
+			AtLeast32BitUnsigned, BlockNumberProvider, IdentifyAccount, SaturatedConversion, Saturating,
+			Zero,
 		},
 		offchain::{
 			storage::StorageValueRef,
 			storage_lock::{BlockAndTime, StorageLock},
 		},
 		transaction_validity::{
-			InvalidTransaction, TransactionSource, TransactionValidity, ValidTransaction,
+			InvalidTransaction, TransactionLongevity, TransactionPriority, TransactionSource,
+			TransactionValidity, ValidTransaction,
 		},
 		RuntimeDebug,
 	};
 	use sp_std::{collections::vec_deque::VecDeque, prelude::*, str};
 
-	use serde::{Deserialize, Deserializer};
+	use serde::Deserialize;
 
 	/// Defines application identifier for crypto keys of this module.
 	///
@@ -44,12 +57,7 @@ pub mod pallet {
 	/// The keys can be inserted manually via RPC (see `author_insertKey`).
 	pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"demo");
 	const NUM_VEC_LEN: usize = 10;
-	/// The type to sign and send transactions.
-	const UNSIGNED_TXS_PRIORITY: u64 = 100;
 
-	// We are fetching information from the github public API about organization`substrate-developer-hub`.
-	const HTTP_REMOTE_REQUEST: &str = "https://api.github.com/orgs/substrate-developer-hub";
-	const HTTP_REMOTE_REQUEST_PRICE: &str = "https://api.coincap.io/v2/assets/polkadot";
 	const HTTP_HEADER_USER_AGENT: &str = "jimmychu0807";
 
 	const FETCH_TIMEOUT_PERIOD: u64 = 3000; // in milli-seconds
@@ -86,88 +94,222 @@ pub mod pallet {
 	}
 
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-	pub struct Payload<Public> {
-		number: u64,
-		public: Public,
+	pub struct Payload<Public, BlockNumber> {
+		pub number: u64,
+		/// The block the payload is signed in, it is stale `PayloadLongevity` blocks later.
+		pub block_number: BlockNumber,
+		pub public: Public,
+	}
+
+	impl<T: SigningTypes> SignedPayload<T> for Payload<T::Public, T::BlockNumber> {
+		fn public(&self) -> T::Public {
+			self.public.clone()
+		}
+	}
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct PricePayload<Public, BlockNumber> {
+		pub symbol: Vec<u8>,
+		/// The round of `symbol` the price is reported for.
+		pub round: u32,
+		pub price: FixedU128,
+		/// The block the payload is signed in, it is stale `PayloadLongevity` blocks later.
+		pub block_number: BlockNumber,
+		pub public: Public,
 	}
 
-	impl<T: SigningTypes> SignedPayload<T> for Payload<T::Public> {
+	impl<T: SigningTypes> SignedPayload<T> for PricePayload<T::Public, T::BlockNumber> {
 		fn public(&self) -> T::Public {
 			self.public.clone()
 		}
 	}
 
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
-	pub struct PricePayload<Public> {
-		integer: u64,
-		decimal: Permill,
-		public: Public,
+	pub struct DocumentPayload<Public, BlockNumber> {
+		/// The attestation source the document is fetched from.
+		pub source: Vec<u8>,
+		pub document: Vec<u8>,
+		/// The block the payload is signed in, it is stale `PayloadLongevity` blocks later.
+		pub block_number: BlockNumber,
+		pub public: Public,
 	}
 
-	impl<T: SigningTypes> SignedPayload<T> for PricePayload<T::Public> {
+	impl<T: SigningTypes> SignedPayload<T> for DocumentPayload<T::Public, T::BlockNumber> {
 		fn public(&self) -> T::Public {
 			self.public.clone()
 		}
 	}
 
-	// ref: https://serde.rs/container-attrs.html#crate
-	#[derive(Deserialize, Encode, Decode, Default)]
-	struct GithubInfo {
-		// Specify our own deserializing function to convert JSON string to vector of bytes
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		login: Vec<u8>,
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		blog: Vec<u8>,
-		public_repos: u32,
+	/// The on-chain commitment to the latest document of an attestation source. The document
+	///   itself is only kept in the offchain database, under `document_key`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct Attestation<BlockNumber> {
+		/// `blake2_256` hash of the document.
+		pub hash: H256,
+		/// The block the document was fetched in.
+		pub block_number: BlockNumber,
+	}
+
+	/// Key of the document of `source` fetched in `block_number`, in the persistent offchain
+	///   storage of the nodes running with offchain indexing.
+	pub fn document_key<BlockNumber: Encode>(source: &[u8], block_number: &BlockNumber) -> Vec<u8> {
+		[&b"offchain-demo::document::"[..], &(source, block_number).encode()].concat()
+	}
+
+	fn is_http_url(url: &[u8]) -> bool {
+		str::from_utf8(url).map_or(false, |url| url.starts_with("http://") || url.starts_with("https://"))
 	}
 
 
 	#[derive(Debug, Deserialize, Encode, Decode, Default)]
 	struct IndexingData(Vec<u8>, u64);
 
-	// price info struct
-	#[derive(Deserialize, Encode, Decode, Default)]
-	struct PriceInfo {
-		data: Data,
+	/// Where the offchain worker fetches the price of an asset from.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct PriceSource {
+		/// Url answering a JSON document.
+		pub url: Vec<u8>,
+		/// JSON pointer (RFC 6901) to the price field of the document, e.g. `/data/priceUsd`.
+		pub json_pointer: Vec<u8>,
 	}
 
-	#[derive(Deserialize, Encode, Decode, Default)]
-	struct Data {
-		#[serde(deserialize_with = "de_string_to_bytes", rename="priceUsd")]
-		price_usd: Vec<u8>,
+	/// The median price of a finalized round.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct AggregatedPrice<BlockNumber> {
+		pub price: FixedU128,
+		/// The round the price was aggregated from.
+		pub round: u32,
+		/// The block in which the round was finalized.
+		pub block_number: BlockNumber,
 	}
 
-	pub fn de_string_to_bytes<'de, D>(de: D) -> Result<Vec<u8>, D::Error>
-	where
-	D: Deserializer<'de>,
-	{
-		let s: &str = Deserialize::deserialize(de)?;
-		Ok(s.as_bytes().to_vec())
+	/// An aggregated price with running sums over the previous aggregated prices of its asset,
+	///   so that the statistics of a window are the difference of two observations.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct Observation<BlockNumber> {
+		pub price: FixedU128,
+		/// The block in which the price was aggregated.
+		pub block_number: BlockNumber,
+		/// Sum of the previous prices, each multiplied by the number of blocks it was the latest.
+		pub price_cumulative: FixedU128,
+		/// Sum of the squared relative changes between consecutive prices.
+		pub squared_change_cumulative: FixedU128,
+		/// Number of price changes summed up in `squared_change_cumulative`.
+		pub changes: u32,
 	}
 
-	impl fmt::Debug for GithubInfo {
-		// `fmt` converts the vector of bytes inside the struct back to string for
-		//   more friendly display.
-		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-			write!(
-				f,
-				"{{ login: {}, blog: {}, public_repos: {} }}",
-				str::from_utf8(&self.login).map_err(|_| fmt::Error)?,
-				str::from_utf8(&self.blog).map_err(|_| fmt::Error)?,
-				&self.public_repos
-			)
+	/// Statistics of the prices of an asset over a window of blocks ending at its last
+	///   aggregated price.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct PriceStats<BlockNumber> {
+		/// Time-weighted average price, each price weighted by the number of blocks it was the
+		///   latest.
+		pub twap: FixedU128,
+		/// Lowest price in effect during the window.
+		pub min: FixedU128,
+		/// Highest price in effect during the window.
+		pub max: FixedU128,
+		/// Root mean square of the relative changes between consecutive prices.
+		pub volatility: FixedU128,
+		/// The block of the last aggregated price.
+		pub block_number: BlockNumber,
+	}
+
+	impl PriceSource {
+		/// Whether the url is http(s) and the JSON pointer is well formed.
+		pub fn is_valid(&self) -> bool {
+			let url_ok = is_http_url(&self.url);
+			let pointer_ok = str::from_utf8(&self.json_pointer)
+				.map_or(false, |pointer| pointer.is_empty() || pointer.starts_with('/'));
+			url_ok && pointer_ok
 		}
 	}
 
-	impl fmt::Debug for PriceInfo {
-		// `fmt` converts the vector of bytes inside the struct back to string for
-		//   more friendly display.
-		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-			write!(
-				f,
-				"{{ priceUsd: {} }}",
-				str::from_utf8(&self.data.price_usd).map_err(|_| fmt::Error)?,
-			)
+	/// The jobs of the offchain worker, each with its own schedule and lock.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum OffchainTask {
+		/// Submit the block number in a signed transaction.
+		SignedTx,
+		/// Submit the block number in an unsigned transaction.
+		UnsignedTx,
+		/// Submit the block number in an unsigned transaction with a signed payload.
+		UnsignedTxSignedPayload,
+		/// Fetch the document of every attestation source and attest the changed ones.
+		Attestation,
+		/// Fetch and submit the price of every registered asset.
+		PriceInfo,
+	}
+
+	impl OffchainTask {
+		pub const ALL: [OffchainTask; 5] = [
+			OffchainTask::SignedTx,
+			OffchainTask::UnsignedTx,
+			OffchainTask::UnsignedTxSignedPayload,
+			OffchainTask::Attestation,
+			OffchainTask::PriceInfo,
+		];
+
+		pub fn name(&self) -> &'static [u8] {
+			match self {
+				OffchainTask::SignedTx => b"signed-tx",
+				OffchainTask::UnsignedTx => b"unsigned-tx",
+				OffchainTask::UnsignedTxSignedPayload => b"unsigned-tx-signed-payload",
+				OffchainTask::Attestation => b"attestation",
+				OffchainTask::PriceInfo => b"price-info",
+			}
+		}
+
+		/// Key of the `StorageLock` held while the task runs.
+		pub fn lock_key(&self) -> Vec<u8> {
+			[&b"offchain-demo::lock::"[..], self.name()].concat()
+		}
+
+		/// Key of the `TaskStatus` in the persistent offchain storage.
+		pub fn status_key(&self) -> Vec<u8> {
+			[&b"offchain-demo::status::"[..], self.name()].concat()
+		}
+
+		/// The schedule used until root sets one: every task runs once every five blocks, each
+		/// at its own offset.
+		pub fn default_schedule<BlockNumber: From<u32>>(&self) -> TaskSchedule<BlockNumber> {
+			let offset = Self::ALL.iter().position(|task| task == self).unwrap_or_default() as u32;
+			TaskSchedule { interval: 5u32.into(), offset: offset.into(), enabled: true }
+		}
+	}
+
+	/// When an offchain task runs: in the blocks whose number is `offset` modulo `interval`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct TaskSchedule<BlockNumber> {
+		pub interval: BlockNumber,
+		/// Always below `interval`.
+		pub offset: BlockNumber,
+		pub enabled: bool,
+	}
+
+	impl<BlockNumber: AtLeast32BitUnsigned + Copy> TaskSchedule<BlockNumber> {
+		/// Whether the interval is set and the offset falls inside of it.
+		pub fn is_valid(&self) -> bool {
+			!self.interval.is_zero() && self.offset < self.interval
+		}
+
+		pub fn is_due(&self, block_number: BlockNumber) -> bool {
+			self.enabled && self.is_valid() && block_number % self.interval == self.offset
+		}
+	}
+
+	/// Record of the runs of an offchain task, kept in the persistent offchain storage under
+	/// `OffchainTask::status_key`.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct TaskStatus<BlockNumber> {
+		/// The block of the last run, successful or not.
+		pub last_run: Option<BlockNumber>,
+		/// The block and error of the last failed run.
+		pub last_error: Option<(BlockNumber, Vec<u8>)>,
+	}
+
+	impl<BlockNumber> Default for TaskStatus<BlockNumber> {
+		fn default() -> Self {
+			Self { last_run: None, last_error: None }
 		}
 	}
 
@@ -179,10 +321,47 @@ pub mod pallet {
 		type Call: From<Call<Self>>;
 		/// The identifier type for an offchain worker.
 		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// Maximum number of price sources of one asset.
+		#[pallet::constant]
+		type MaxSourcesPerAsset: Get<u32>;
+		/// Maximum number of price reporters.
+		#[pallet::constant]
+		type MaxReporters: Get<u32>;
+		/// Number of reporters that must submit a price before a round is finalized.
+		#[pallet::constant]
+		type Quorum: Get<u32>;
+		/// Reporters whose price deviates from the median by more than this ratio are flagged.
+		#[pallet::constant]
+		type MaxDeviation: Get<Permill>;
+		/// Number of blocks an authority waits before its next unsigned transaction of the same
+		/// kind, i.e. numbers or the price of one asset.
+		#[pallet::constant]
+		type UnsignedInterval: Get<Self::BlockNumber>;
+		/// Number of blocks a signed payload stays valid after the block it is signed in.
+		#[pallet::constant]
+		type PayloadLongevity: Get<Self::BlockNumber>;
+		/// Base priority of unsigned transactions. Price submissions add the number of blocks
+		/// since the price of the asset was last aggregated.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+		/// Windows, in blocks, over which the price statistics of each asset are kept.
+		#[pallet::constant]
+		type StatsWindows: Get<Vec<Self::BlockNumber>>;
+		/// Number of aggregated prices kept per asset for the statistics. Windows reaching
+		/// further back only cover the kept prices.
+		#[pallet::constant]
+		type MaxObservations: Get<u32>;
+		/// Maximum length in bytes of an attested document.
+		#[pallet::constant]
+		type MaxDocumentLen: Get<u32>;
 	}
 
+	/// Version 1 keeps the prices per asset symbol, see `migrations::v1`.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	// The pallet's runtime storage items.
@@ -193,29 +372,176 @@ pub mod pallet {
 	// https://substrate.dev/docs/en/knowledgebase/runtime/storage#declaring-storage-items
 	pub type Numbers<T> = StorageValue<_, VecDeque<u64>, ValueQuery>;
 
+	/// Price sources of each asset symbol, tried in order until one of them answers.
+	#[pallet::storage]
+	#[pallet::getter(fn price_sources)]
+	pub type PriceSources<T> = StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<PriceSource>, ValueQuery>;
+
+	/// Accounts allowed to report prices, identified by the key signing the price payloads.
+	#[pallet::storage]
+	#[pallet::getter(fn reporters)]
+	pub type Reporters<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// The round collecting submissions of each asset symbol.
+	#[pallet::storage]
+	#[pallet::getter(fn current_round)]
+	pub type CurrentRound<T> = StorageMap<_, Blake2_128Concat, Vec<u8>, u32, ValueQuery>;
+
+	/// Prices submitted in the current round, keyed by asset symbol then reporter.
+	#[pallet::storage]
+	#[pallet::getter(fn submissions)]
+	pub type Submissions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		T::AccountId,
+		FixedU128,
+	>;
+
+	/// The median price of the last finalized round of each asset symbol.
+	#[pallet::storage]
+	#[pallet::getter(fn aggregated_price)]
+	pub type AggregatedPrices<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, AggregatedPrice<T::BlockNumber>>;
+
+	/// Number of rounds in which a reporter deviated from the median beyond `MaxDeviation`.
+	#[pallet::storage]
+	#[pallet::getter(fn deviations)]
+	pub type Deviations<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The block from which `submit_number_unsigned` is accepted again.
+	#[pallet::storage]
+	#[pallet::getter(fn next_unsigned_at)]
+	pub type NextUnsignedAt<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	/// The block from which an authority may submit its next signed payload, keyed by authority
+	/// then asset symbol, `None` for numbers.
+	#[pallet::storage]
+	#[pallet::getter(fn next_signed_payload_at)]
+	pub type NextSignedPayloadAt<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		Option<Vec<u8>>,
+		T::BlockNumber,
+		ValueQuery,
+	>;
+
+	/// Schedules set by root, tasks without one follow `OffchainTask::default_schedule`.
+	#[pallet::storage]
+	pub type TaskSchedules<T: Config> =
+		StorageMap<_, Blake2_128Concat, OffchainTask, TaskSchedule<T::BlockNumber>>;
+
+	/// The latest aggregated prices of each asset symbol.
 	#[pallet::storage]
 	#[pallet::getter(fn prices)]
-	pub type Prices<T> = StorageValue<_, VecDeque<(u64, Permill)>, ValueQuery>;
+	pub type Prices<T> = StorageMap<_, Blake2_128Concat, Vec<u8>, VecDeque<FixedU128>, ValueQuery>;
+
+	/// The latest `MaxObservations` aggregated prices of each asset symbol, oldest first.
+	#[pallet::storage]
+	#[pallet::getter(fn observations)]
+	pub type Observations<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, VecDeque<Observation<T::BlockNumber>>, ValueQuery>;
+
+	/// Statistics of each asset symbol over each window of `StatsWindows`, updated on every
+	/// aggregated price.
+	#[pallet::storage]
+	#[pallet::getter(fn price_statistics)]
+	pub type PriceStatistics<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Twox64Concat,
+		T::BlockNumber,
+		PriceStats<T::BlockNumber>,
+	>;
+
+	/// Url of the JSON document of each attestation source.
+	#[pallet::storage]
+	#[pallet::getter(fn attestation_source)]
+	pub type AttestationSources<T> = StorageMap<_, Blake2_128Concat, Vec<u8>, Vec<u8>>;
+
+	/// The latest attestation of each attestation source.
+	#[pallet::storage]
+	#[pallet::getter(fn attestation)]
+	pub type Attestations<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Attestation<T::BlockNumber>>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Initial price sources as `(symbol, url, json_pointer)`.
+		pub price_sources: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)>,
+		/// Initial price reporters.
+		pub reporters: Vec<T::AccountId>,
+		/// Initial attestation sources as `(source, url)`.
+		pub attestation_sources: Vec<(Vec<u8>, Vec<u8>)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { price_sources: Vec::new(), reporters: Vec::new(), attestation_sources: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (symbol, url, json_pointer) in self.price_sources.iter() {
+				let source = PriceSource { url: url.clone(), json_pointer: json_pointer.clone() };
+				assert!(source.is_valid(), "invalid price source in genesis");
+				PriceSources::<T>::append(symbol, source);
+			}
+			assert!(
+				self.reporters.len() as u32 <= T::MaxReporters::get(),
+				"too many reporters in genesis"
+			);
+			Reporters::<T>::put(&self.reporters);
+			for (source, url) in self.attestation_sources.iter() {
+				assert!(is_http_url(url), "invalid attestation source in genesis");
+				AttestationSources::<T>::insert(source, url);
+			}
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		NewNumber(Option<T::AccountId>, u64),
-		NewPrice(Option<T::AccountId>, u64, Permill)
+		/// \[who, symbol, price\]
+		NewPrice(Option<T::AccountId>, Vec<u8>, FixedU128),
+		/// \[symbol, source\]
+		PriceSourceAdded(Vec<u8>, PriceSource),
+		/// \[symbol, source\]
+		PriceSourceRemoved(Vec<u8>, PriceSource),
+		/// \[reporter\]
+		ReporterAdded(T::AccountId),
+		/// \[reporter\]
+		ReporterRemoved(T::AccountId),
+		/// A round is finalized to its median price. \[symbol, round, price\]
+		PriceAggregated(Vec<u8>, u32, FixedU128),
+		/// A reporter deviated from the median too much. \[reporter, symbol, round\]
+		ReporterFlagged(T::AccountId, Vec<u8>, u32),
+		/// \[task, schedule\]
+		TaskScheduleSet(OffchainTask, TaskSchedule<T::BlockNumber>),
+		/// \[source, url\]
+		AttestationSourceSet(Vec<u8>, Option<Vec<u8>>),
+		/// \[source, hash, block_number\]
+		DocumentAttested(Vec<u8>, H256, T::BlockNumber),
 	}
 
 	// Errors inform users that something went wrong.
 	#[pallet::error]
 	pub enum Error<T> {
-		// Error returned when not sure which ocw function to executed
-		UnknownOffchainMux,
-
 		// Error returned when making signed transactions in off-chain worker
 		NoLocalAcctForSigning,
 		OffchainSignedTxError,
 
 		// Error returned when making unsigned transactions in off-chain worker
 		OffchainUnsignedTxError,
+		UnsignedTxTooEarly,
 
 		// Error returned when making unsigned transactions with signed payloads in off-chain worker
 		OffchainUnsignedTxSignedPayloadError,
@@ -225,6 +551,35 @@ pub mod pallet {
 
 		// Error returned when fetching github info
 		HttpFetchingError,
+
+		// Error returned when the price field is missing in the fetched document
+		PriceNotFound,
+		// Error returned when the price field is not a non-negative decimal number
+		InvalidPrice,
+
+		// Errors returned when managing price sources
+		InvalidPriceSource,
+		DuplicatePriceSource,
+		TooManyPriceSources,
+		PriceSourceNotExist,
+
+		// Errors returned when managing reporters or submitting prices
+		NotReporter,
+		AlreadyReporter,
+		TooManyReporters,
+		WrongRound,
+		AlreadySubmitted,
+
+		// Error returned when the interval of a task schedule is zero or below its offset
+		InvalidSchedule,
+
+		// Errors returned when managing attestation sources or attesting documents
+		InvalidAttestationSource,
+		AttestationSourceNotExist,
+		DocumentTooLarge,
+		InvalidDocument,
+		StaleDocument,
+		DocumentOffchainUnsignedTxSignedPayloadError,
 	}
 
 	#[pallet::hooks]
@@ -245,21 +600,20 @@ pub mod pallet {
 			// 1. Sending signed transaction from ocw
 			// 2. Sending unsigned transaction from ocw
 			// 3. Sending unsigned transactions with signed payloads from ocw
-			// 4. Fetching JSON via http requests in ocw
-			const TX_TYPES: u32 = 5;
-			let modu = block_number.try_into().map_or(TX_TYPES, |bn: usize| (bn as u32) % TX_TYPES);
-			let result = match modu {
-				0 => Self::offchain_signed_tx(block_number),
-				1 => Self::offchain_unsigned_tx(block_number),
-				2 => Self::offchain_unsigned_tx_signed_payload(block_number),
-				3 => Self::fetch_github_info(),
-				4 => Self::fetch_price_info(),
-				_ => Err(Error::<T>::UnknownOffchainMux),
-			};
+			// 4. Fetching JSON via http requests in ocw, and attesting it on-chain
+			// Each of them is a task running on its own schedule, see `set_task_schedule`.
+			for task in OffchainTask::ALL.iter() {
+				if Self::task_schedule(*task).is_due(block_number) {
+					Self::run_task(*task, block_number);
+				}
+			}
+		}
 
-			if let Err(e) = result {
-				log::error!("offchain_worker error: {:?}", e);
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() < 1 {
+				return crate::migrations::v1::migrate::<T>();
 			}
+			0
 		}
 	}
 
@@ -275,26 +629,84 @@ pub mod pallet {
 		fn validate_unsigned(_source: TransactionSource, call: &Self::Call)
 		-> TransactionValidity
 		{
-			let valid_tx = |provide| ValidTransaction::with_tag_prefix("ocw-demo")
-			.priority(UNSIGNED_TXS_PRIORITY)
+			let valid_tx = |provide, priority, longevity| ValidTransaction::with_tag_prefix("ocw-demo")
+			.priority(priority)
 			.and_provides([&provide])
-			.longevity(3)
+			.longevity(longevity)
 			.propagate(true)
 			.build();
+			let now = <frame_system::Pallet<T>>::block_number();
 
 			match call {
-				Call::submit_number_unsigned(_number) => valid_tx(b"submit_number_unsigned".to_vec()),
+				Call::submit_number_unsigned(_number) => {
+					// anyone can send it, so only one is accepted every `UnsignedInterval` blocks
+					if Self::next_unsigned_at() > now {
+						return InvalidTransaction::Stale.into();
+					}
+					valid_tx(b"submit_number_unsigned".to_vec(), T::UnsignedPriority::get(), 3)
+				},
 				Call::submit_number_unsigned_with_signed_payload(ref payload, ref signature) => {
 					if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
 						return InvalidTransaction::BadProof.into();
 					}
-					valid_tx(b"submit_number_unsigned_with_signed_payload".to_vec())
+					let longevity = Self::check_payload_block_number(payload.block_number)?;
+					let authority = payload.public.clone().into_account();
+					if Self::next_signed_payload_at(&authority, None::<Vec<u8>>) > now {
+						return InvalidTransaction::Stale.into();
+					}
+					// one number per authority can be in the pool at the same time
+					let provide = authority.encode();
+					valid_tx(
+						[&b"submit_number_unsigned_with_signed_payload"[..], &provide].concat(),
+						T::UnsignedPriority::get(),
+						longevity,
+					)
 				},
 				Call::submit_price_unsigned_with_signed_payload(ref price_payload, ref signature) => {
 					if !SignedPayload::<T>::verify::<T::AuthorityId>(price_payload, signature.clone()) {
 						return InvalidTransaction::BadProof.into();
 					}
-					valid_tx(b"submit_price_unsigned_with_signed_payload".to_vec())
+					let longevity = Self::check_payload_block_number(price_payload.block_number)?;
+					let reporter = match Self::check_price_payload(price_payload) {
+						Ok(reporter) => reporter,
+						Err(Error::<T>::NotReporter) => return InvalidTransaction::BadSigner.into(),
+						Err(Error::<T>::PriceSourceNotExist) => return InvalidTransaction::Call.into(),
+						Err(_) => return InvalidTransaction::Stale.into(),
+					};
+					if Self::next_signed_payload_at(&reporter, Some(price_payload.symbol.clone())) > now {
+						return InvalidTransaction::Stale.into();
+					}
+					// the older the on-chain price, the sooner a new one should be included
+					let updated_at = Self::aggregated_price(&price_payload.symbol)
+						.map_or_else(Zero::zero, |price| price.block_number);
+					let staleness: TransactionPriority = now.saturating_sub(updated_at).saturated_into();
+					// one price per asset, round and reporter can be in the pool at the same time
+					let provide = (&price_payload.symbol, price_payload.round, reporter).encode();
+					valid_tx(
+						[&b"submit_price_unsigned_with_signed_payload"[..], &provide].concat(),
+						T::UnsignedPriority::get().saturating_add(staleness),
+						longevity,
+					)
+				},
+				Call::submit_document_unsigned_with_signed_payload(ref payload, ref signature) => {
+					if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
+						return InvalidTransaction::BadProof.into();
+					}
+					let longevity = Self::check_payload_block_number(payload.block_number)?;
+					match Self::check_document_payload(payload) {
+						Ok(()) => {},
+						Err(Error::<T>::NotReporter) => return InvalidTransaction::BadSigner.into(),
+						Err(Error::<T>::AttestationSourceNotExist) => return InvalidTransaction::Call.into(),
+						Err(Error::<T>::DocumentTooLarge) => return InvalidTransaction::ExhaustsResources.into(),
+						Err(_) => return InvalidTransaction::Stale.into(),
+					}
+					// one document per source and block can be in the pool at the same time
+					let provide = (&payload.source, payload.block_number).encode();
+					valid_tx(
+						[&b"submit_document_unsigned_with_signed_payload"[..], &provide].concat(),
+						T::UnsignedPriority::get(),
+						longevity,
+					)
 				},
 				_ => InvalidTransaction::Call.into(),
 			}
@@ -318,43 +730,246 @@ pub mod pallet {
 			let _ = ensure_none(origin)?;
 			log::info!("submit_number_unsigned: {}", number);
 			Self::append_or_replace_number(number);
+			NextUnsignedAt::<T>::put(Self::next_submission_block());
 
 			Self::deposit_event(Event::NewNumber(None, number));
 			Ok(())
 		}
 
 		#[pallet::weight(10000)]
-		pub fn submit_number_unsigned_with_signed_payload(origin: OriginFor<T>, payload: Payload<T::Public>,
-			_signature: T::Signature) -> DispatchResult
+		pub fn submit_number_unsigned_with_signed_payload(origin: OriginFor<T>,
+			payload: Payload<T::Public, T::BlockNumber>, _signature: T::Signature) -> DispatchResult
 		{
 			let _ = ensure_none(origin)?;
 			// we don't need to verify the signature here because it has been verified in
 			//   `validate_unsigned` function when sending out the unsigned tx.
-			let Payload { number, public } = payload;
+			let Payload { number, public, .. } = payload;
 			log::info!("submit_number_unsigned_with_signed_payload: ({}, {:?})", number, public);
 			Self::append_or_replace_number(number);
+			NextSignedPayloadAt::<T>::insert(public.into_account(), None::<Vec<u8>>, Self::next_submission_block());
 
 			Self::deposit_event(Event::NewNumber(None, number));
 			Ok(())
 		}
 
 		#[pallet::weight(10000)]
-		pub fn submit_price_unsigned_with_signed_payload(origin: OriginFor<T>, price_payload: PricePayload<T::Public>,
-			_signature: T::Signature) -> DispatchResult
+		pub fn submit_price_unsigned_with_signed_payload(origin: OriginFor<T>,
+			price_payload: PricePayload<T::Public, T::BlockNumber>, _signature: T::Signature) -> DispatchResult
 		{
 			let _ = ensure_none(origin)?;
 			// we don't need to verify the signature here because it has been verified in
 			//   `validate_unsigned` function when sending out the unsigned tx.
-			let PricePayload { integer, decimal, public } = price_payload;
-			log::info!("submit_price_unsigned_with_signed_payload: ({}, {:?}, {:?})", integer, decimal, public);
-			Self::append_or_replace_price((integer, decimal));
+			let reporter = Self::check_price_payload(&price_payload)?;
+			let PricePayload { symbol, round, price, public, .. } = price_payload;
+			log::info!("submit_price_unsigned_with_signed_payload: ({:?}, {}, {:?}, {:?})", symbol, round, price, public);
+			Submissions::<T>::insert(&symbol, &reporter, price);
+			NextSignedPayloadAt::<T>::insert(&reporter, Some(symbol.clone()), Self::next_submission_block());
+
+			Self::deposit_event(Event::NewPrice(Some(reporter), symbol.clone(), price));
+
+			let reporters = Self::reporters();
+			let submitted = Submissions::<T>::iter_prefix(&symbol).filter(|(who, _)| reporters.contains(who)).count();
+			if submitted as u32 >= T::Quorum::get() {
+				Self::finalize_round(&symbol, round);
+			}
+			Ok(())
+		}
+
+		/// Allow `who` to report prices.
+		#[pallet::weight(10000)]
+		pub fn add_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+
+			Reporters::<T>::try_mutate(|reporters| {
+				ensure!(!reporters.contains(&who), Error::<T>::AlreadyReporter);
+				ensure!((reporters.len() as u32) < T::MaxReporters::get(), Error::<T>::TooManyReporters);
+				reporters.push(who.clone());
+				Ok::<(), Error<T>>(())
+			})?;
 
-			Self::deposit_event(Event::NewPrice(None, integer, decimal));
+			Self::deposit_event(Event::ReporterAdded(who));
+			Ok(())
+		}
+
+		/// Disallow `who` to report prices. Its submissions to unfinalized rounds are ignored.
+		#[pallet::weight(10000)]
+		pub fn remove_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			ensure_root(origin)?;
+
+			Reporters::<T>::try_mutate(|reporters| {
+				let index = reporters.iter().position(|r| r == &who).ok_or(Error::<T>::NotReporter)?;
+				reporters.swap_remove(index);
+				Ok::<(), Error<T>>(())
+			})?;
+
+			Self::deposit_event(Event::ReporterRemoved(who));
+			Ok(())
+		}
+
+		/// Register a price source for the asset `symbol`.
+		#[pallet::weight(10000)]
+		pub fn add_price_source(origin: OriginFor<T>, symbol: Vec<u8>, url: Vec<u8>,
+			json_pointer: Vec<u8>) -> DispatchResult
+		{
+			ensure_root(origin)?;
+			let source = PriceSource { url, json_pointer };
+			ensure!(!symbol.is_empty() && source.is_valid(), Error::<T>::InvalidPriceSource);
+
+			PriceSources::<T>::try_mutate(&symbol, |sources| {
+				ensure!(!sources.contains(&source), Error::<T>::DuplicatePriceSource);
+				ensure!((sources.len() as u32) < T::MaxSourcesPerAsset::get(), Error::<T>::TooManyPriceSources);
+				sources.push(source.clone());
+				Ok::<(), Error<T>>(())
+			})?;
+
+			Self::deposit_event(Event::PriceSourceAdded(symbol, source));
+			Ok(())
+		}
+
+		/// Remove the price source at `index` of the asset `symbol`.
+		#[pallet::weight(10000)]
+		pub fn remove_price_source(origin: OriginFor<T>, symbol: Vec<u8>, index: u32) -> DispatchResult {
+			ensure_root(origin)?;
+
+			let source = PriceSources::<T>::try_mutate_exists(&symbol, |maybe_sources| {
+				let sources = maybe_sources.as_mut().ok_or(Error::<T>::PriceSourceNotExist)?;
+				ensure!((index as usize) < sources.len(), Error::<T>::PriceSourceNotExist);
+				let source = sources.remove(index as usize);
+				if sources.is_empty() {
+					*maybe_sources = None;
+				}
+				Ok::<_, Error<T>>(source)
+			})?;
+
+			Self::deposit_event(Event::PriceSourceRemoved(symbol, source));
+			Ok(())
+		}
+
+		/// Set the url of the JSON document of `source`, or remove the source with `None`.
+		#[pallet::weight(10000)]
+		pub fn set_attestation_source(origin: OriginFor<T>, source: Vec<u8>, url: Option<Vec<u8>>)
+			-> DispatchResult
+		{
+			ensure_root(origin)?;
+			ensure!(!source.is_empty(), Error::<T>::InvalidAttestationSource);
+
+			match url {
+				Some(ref url) => {
+					ensure!(is_http_url(url), Error::<T>::InvalidAttestationSource);
+					AttestationSources::<T>::insert(&source, url);
+				},
+				None => {
+					ensure!(AttestationSources::<T>::contains_key(&source), Error::<T>::AttestationSourceNotExist);
+					AttestationSources::<T>::remove(&source);
+				},
+			}
+
+			Self::deposit_event(Event::AttestationSourceSet(source, url));
+			Ok(())
+		}
+
+		/// Attest a document fetched by a reporter: its hash goes to the state, the document
+		///   itself to the offchain database of the nodes running with offchain indexing.
+		#[pallet::weight(10000)]
+		pub fn submit_document_unsigned_with_signed_payload(origin: OriginFor<T>,
+			payload: DocumentPayload<T::Public, T::BlockNumber>, _signature: T::Signature) -> DispatchResult
+		{
+			let _ = ensure_none(origin)?;
+			// the signature has been verified in `validate_unsigned`
+			Self::check_document_payload(&payload)?;
+			let DocumentPayload { source, document, block_number, .. } = payload;
+			let hash = H256(sp_io::hashing::blake2_256(&document));
+			log::info!("submit_document_unsigned_with_signed_payload: ({:?}, {:?})", source, hash);
+
+			sp_io::offchain_index::set(&document_key(&source, &block_number), &document);
+			Attestations::<T>::insert(&source, Attestation { hash, block_number });
+
+			Self::deposit_event(Event::DocumentAttested(source, hash, block_number));
+			Ok(())
+		}
+
+		/// Set when `task` runs, root only.
+		#[pallet::weight(10000)]
+		pub fn set_task_schedule(origin: OriginFor<T>, task: OffchainTask,
+			schedule: TaskSchedule<T::BlockNumber>) -> DispatchResult
+		{
+			ensure_root(origin)?;
+			ensure!(schedule.is_valid(), Error::<T>::InvalidSchedule);
+
+			TaskSchedules::<T>::insert(task, &schedule);
+
+			Self::deposit_event(Event::TaskScheduleSet(task, schedule));
 			Ok(())
 		}
 	}
 
 	impl<T: Config> Pallet<T> {
+		/// The schedule of `task`, the default one unless root has set it.
+		pub fn task_schedule(task: OffchainTask) -> TaskSchedule<T::BlockNumber> {
+			TaskSchedules::<T>::get(task).unwrap_or_else(|| task.default_schedule())
+		}
+
+		/// The schedules of all the tasks.
+		pub fn task_schedules() -> Vec<(OffchainTask, TaskSchedule<T::BlockNumber>)> {
+			OffchainTask::ALL.iter().map(|task| (*task, Self::task_schedule(*task))).collect()
+		}
+
+		/// The statistics of `symbol` over each window of `StatsWindows`, as `(window, stats)`.
+		pub fn price_stats(symbol: &[u8]) -> Vec<(T::BlockNumber, PriceStats<T::BlockNumber>)> {
+			T::StatsWindows::get()
+				.into_iter()
+				.filter_map(|window| Self::price_statistics(symbol, window).map(|stats| (window, stats)))
+				.collect()
+		}
+
+		/// Run `task` unless a previous run of it still holds its lock, and record the outcome.
+		fn run_task(task: OffchainTask, block_number: T::BlockNumber) {
+			// Since off-chain storage can be accessed by off-chain workers from multiple runs, it is important to lock
+			//   it before doing heavy computations or write operations. Every task has its own lock, so that a
+			//   slow task does not hold the others back.
+			//
+			// There are four ways of defining a lock:
+			//   1) `new` - lock with default time and block exipration
+			//   2) `with_deadline` - lock with default block but custom time expiration
+			//   3) `with_block_deadline` - lock with default time but custom block expiration
+			//   4) `with_block_and_time_deadline` - lock with custom time and block expiration
+			// Here we choose the most custom one for demonstration purpose.
+			let lock_key = task.lock_key();
+			let mut lock = StorageLock::<BlockAndTime<Self>>::with_block_and_time_deadline(
+				&lock_key, LOCK_BLOCK_EXPIRATION,
+				rt_offchain::Duration::from_millis(LOCK_TIMEOUT_EXPIRATION)
+			);
+			// If the lock is held, a previous run of the task is still going on.
+			let _guard = match lock.try_lock() {
+				Ok(guard) => guard,
+				Err(_) => {
+					log::info!("offchain task {:?} is still running", task);
+					return;
+				}
+			};
+
+			let result = match task {
+				OffchainTask::SignedTx => Self::offchain_signed_tx(block_number),
+				OffchainTask::UnsignedTx => Self::offchain_unsigned_tx(block_number),
+				OffchainTask::UnsignedTxSignedPayload => Self::offchain_unsigned_tx_signed_payload(block_number),
+				OffchainTask::Attestation => Self::attest_documents(block_number),
+				OffchainTask::PriceInfo => Self::fetch_price_info(block_number),
+			};
+			if let Err(e) = &result {
+				log::error!("offchain task {:?} error: {:?}", task, e);
+			}
+
+			// Only the holder of the task lock writes the status, no need for `mutate`.
+			let status_ref = StorageValueRef::persistent(&task.status_key());
+			let mut status = status_ref.get::<TaskStatus<T::BlockNumber>>().ok().flatten().unwrap_or_default();
+			status.last_run = Some(block_number);
+			if let Err(e) = result {
+				let error: &'static str = e.into();
+				status.last_error = Some((block_number, error.as_bytes().to_vec()));
+			}
+			status_ref.set(&status);
+		}
+
 		/// Append a new number to the tail of the list, removing an element from the head if reaching
 		///   the bounded length.
 		fn append_or_replace_number(number: u64) {
@@ -367,8 +982,101 @@ pub mod pallet {
 			});
 		}
 
-		fn append_or_replace_price(price: (u64, Permill)) {
-			Prices::<T>::mutate(|prices| {
+		/// The block from which the sender of an unsigned transaction included now may send the
+		///   next one.
+		fn next_submission_block() -> T::BlockNumber {
+			<frame_system::Pallet<T>>::block_number().saturating_add(T::UnsignedInterval::get())
+		}
+
+		/// Reject payloads signed in a future block or more than `PayloadLongevity` blocks ago,
+		///   so that they cannot be replayed later on. Returns the number of blocks the payload
+		///   stays valid for.
+		fn check_payload_block_number(block_number: T::BlockNumber)
+			-> Result<TransactionLongevity, InvalidTransaction>
+		{
+			let now = <frame_system::Pallet<T>>::block_number();
+			if block_number > now {
+				return Err(InvalidTransaction::Future);
+			}
+			let expiry = block_number.saturating_add(T::PayloadLongevity::get());
+			if expiry <= now {
+				return Err(InvalidTransaction::Stale);
+			}
+			Ok(expiry.saturating_sub(now).saturated_into())
+		}
+
+		/// Check that the payload comes from a reporter which has not yet reported the current
+		///   round of a registered asset, and return the reporter.
+		fn check_price_payload(payload: &PricePayload<T::Public, T::BlockNumber>) -> Result<T::AccountId, Error<T>> {
+			ensure!(PriceSources::<T>::contains_key(&payload.symbol), Error::<T>::PriceSourceNotExist);
+			let reporter = payload.public.clone().into_account();
+			ensure!(Self::reporters().contains(&reporter), Error::<T>::NotReporter);
+			ensure!(payload.round == Self::current_round(&payload.symbol), Error::<T>::WrongRound);
+			ensure!(
+				!Submissions::<T>::contains_key(&payload.symbol, &reporter),
+				Error::<T>::AlreadySubmitted
+			);
+			Ok(reporter)
+		}
+
+		/// Check that the document comes from a reporter, for a registered source, and that it
+		///   is newer than the attested one.
+		fn check_document_payload(payload: &DocumentPayload<T::Public, T::BlockNumber>) -> Result<(), Error<T>> {
+			ensure!(AttestationSources::<T>::contains_key(&payload.source), Error::<T>::AttestationSourceNotExist);
+			let reporter = payload.public.clone().into_account();
+			ensure!(Self::reporters().contains(&reporter), Error::<T>::NotReporter);
+			ensure!(payload.document.len() as u32 <= T::MaxDocumentLen::get(), Error::<T>::DocumentTooLarge);
+			let newer = Self::attestation(&payload.source)
+				.map_or(true, |attestation| payload.block_number > attestation.block_number);
+			ensure!(newer, Error::<T>::StaleDocument);
+			Ok(())
+		}
+
+		/// Aggregate the submissions of `round` to their median, flag the reporters deviating
+		///   from it and open the next round.
+		fn finalize_round(symbol: &[u8], round: u32) {
+			let reporters = Self::reporters();
+			// submissions of removed reporters are dropped
+			let submissions: Vec<(T::AccountId, u128)> = Submissions::<T>::drain_prefix(symbol)
+				.filter(|(who, _)| reporters.contains(who))
+				.map(|(who, price)| (who, price.into_inner()))
+				.collect();
+			if submissions.is_empty() {
+				return;
+			}
+
+			let mut values: Vec<u128> = submissions.iter().map(|(_, value)| *value).collect();
+			values.sort_unstable();
+			let mid = values.len() / 2;
+			let median = if values.len() % 2 == 0 {
+				values[mid - 1] + (values[mid] - values[mid - 1]) / 2
+			} else {
+				values[mid]
+			};
+
+			let threshold = T::MaxDeviation::get().mul_floor(median);
+			for (who, value) in submissions {
+				if value.max(median) - value.min(median) > threshold {
+					Deviations::<T>::mutate(&who, |count| *count = count.saturating_add(1));
+					Self::deposit_event(Event::ReporterFlagged(who, symbol.to_vec(), round));
+				}
+			}
+
+			let price = FixedU128::from_inner(median);
+			AggregatedPrices::<T>::insert(symbol, AggregatedPrice {
+				price,
+				round,
+				block_number: <frame_system::Pallet<T>>::block_number(),
+			});
+			Self::append_or_replace_price(symbol, price);
+			Self::record_observation(symbol, price);
+			CurrentRound::<T>::insert(symbol, round.wrapping_add(1));
+
+			Self::deposit_event(Event::PriceAggregated(symbol.to_vec(), round, price));
+		}
+
+		fn append_or_replace_price(symbol: &[u8], price: FixedU128) {
+			Prices::<T>::mutate(symbol, |prices| {
 				if prices.len() == NUM_VEC_LEN {
 					let _ = prices.pop_front();
 				}
@@ -377,6 +1085,48 @@ pub mod pallet {
 			});
 		}
 
+		/// Extend the running sums of `symbol` with `price` and update its statistics over every
+		///   window.
+		fn record_observation(symbol: &[u8], price: FixedU128) {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let mut observations = Self::observations(symbol);
+			let observation = match observations.back() {
+				Some(last) => {
+					let elapsed: u128 = now.saturating_sub(last.block_number).saturated_into();
+					let change = relative_change(last.price, price);
+					Observation {
+						price,
+						block_number: now,
+						price_cumulative: FixedU128::from_inner(
+							last.price_cumulative.into_inner()
+								.saturating_add(last.price.into_inner().saturating_mul(elapsed)),
+						),
+						squared_change_cumulative: last.squared_change_cumulative
+							.saturating_add(change.saturating_mul(change)),
+						changes: last.changes.saturating_add(1),
+					}
+				}
+				None => Observation {
+					price,
+					block_number: now,
+					price_cumulative: Zero::zero(),
+					squared_change_cumulative: Zero::zero(),
+					changes: 0,
+				},
+			};
+			while observations.len() as u32 >= T::MaxObservations::get().max(1) {
+				let _ = observations.pop_front();
+			}
+			observations.push_back(observation);
+
+			for window in T::StatsWindows::get() {
+				if let Some(stats) = window_stats(&observations, window) {
+					PriceStatistics::<T>::insert(symbol, window, stats);
+				}
+			}
+			Observations::<T>::insert(symbol, observations);
+		}
+
 		/// This function uses the `offchain::http` API to query the remote github information,
 		///   and returns the JSON response as vector of bytes.
 		// let fetch from remote reuse by different http request
@@ -416,120 +1166,130 @@ pub mod pallet {
 			Ok(response.body().collect::<Vec<u8>>())
 		}
 
-		/// Check if we have fetched github info before. If yes, we can use the cached version
-		///   stored in off-chain worker storage `storage`. If not, we fetch the remote info and
-		///   write the info into the storage for future retrieval.
-		fn fetch_github_info() -> Result<(), Error<T>> {
-			// Create a reference to Local Storage value.
-			// Since the local storage is common for all offchain workers, it's a good practice
-			// to prepend our entry with the pallet name.
-			let s_info = StorageValueRef::persistent(b"offchain-demo::gh-info");
-
-			// Local storage is persisted and shared between runs of the offchain workers,
-			// offchain workers may run concurrently. We can use the `mutate` function to
-			// write a storage entry in an atomic fashion.
-			//
-			// With a similar API as `StorageValue` with the variables `get`, `set`, `mutate`.
-			// We will likely want to use `mutate` to access
-			// the storage comprehensively.
-			//
-			if let Ok(Some(gh_info)) = s_info.get::<GithubInfo>() {
-				// gh-info has already been fetched. Return early.
-				log::info!("cached gh-info: {:?}", gh_info);
-				return Ok(());
-			}
-
-			// Since off-chain storage can be accessed by off-chain workers from multiple runs, it is important to lock
-			//   it before doing heavy computations or write operations.
-			//
-			// There are four ways of defining a lock:
-			//   1) `new` - lock with default time and block exipration
-			//   2) `with_deadline` - lock with default block but custom time expiration
-			//   3) `with_block_deadline` - lock with default time but custom block expiration
-			//   4) `with_block_and_time_deadline` - lock with custom time and block expiration
-			// Here we choose the most custom one for demonstration purpose.
-			let mut lock = StorageLock::<BlockAndTime<Self>>::with_block_and_time_deadline(
-				b"offchain-demo::lock", LOCK_BLOCK_EXPIRATION,
-				rt_offchain::Duration::from_millis(LOCK_TIMEOUT_EXPIRATION)
-			);
-
-			// We try to acquire the lock here. If failed, we know the `fetch_n_parse` part inside is being
-			//   executed by previous run of ocw, so the function just returns.
-			if let Ok(_guard) = lock.try_lock() {
-				match Self::fetch_n_parse(HTTP_REMOTE_REQUEST) {
-					Ok(gh_info) => { s_info.set(&gh_info); }
-					Err(err) => { return Err(err); }
+		/// Fetch the document of every attestation source and submit the changed ones on-chain.
+		fn attest_documents(block_number: T::BlockNumber) -> Result<(), Error<T>> {
+			// A failing source does not keep the others from being attested.
+			let mut result = Ok(());
+			for (source, url) in AttestationSources::<T>::iter() {
+				if let Err(err) = Self::fetch_document(&url)
+					.and_then(|document| Self::submit_document(source.clone(), document, block_number))
+				{
+					log::error!("attest document of {:?} error: {:?}", str::from_utf8(&source), err);
+					result = Err(err);
 				}
 			}
-			Ok(())
+			result
 		}
 
-		/// Fetch from remote and deserialize the JSON to a struct
-		fn fetch_n_parse(http_addr: &str) -> Result<GithubInfo, Error<T>> {
-			let resp_bytes = Self::fetch_from_remote(&http_addr).map_err(|e| {
-				log::error!("fetch_from_remote error: {:?}", e);
-				<Error<T>>::HttpFetchingError
-			})?;
-			let resp_str = str::from_utf8(&resp_bytes).map_err(|_| <Error<T>>::HttpFetchingError)?;
-			// Print out our fetched JSON string
-			log::info!("{}", resp_str);
-			// Deserializing JSON to struct, thanks to `serde` and `serde_derive`
-			let json_info = serde_json::from_str(&resp_str).map_err(|_| <Error<T>>::HttpFetchingError)?;
-			Ok(json_info)
-		}
-
-		fn fetch_n_parse_price(http_addr: &str) -> Result<PriceInfo, Error<T>> {
-			let resp_bytes = Self::fetch_from_remote(&http_addr).map_err(|e| {
-				log::error!("fetch_from_remote error: {:?}", e);
-				<Error<T>>::HttpFetchingError
-			})?;
-			let resp_str = str::from_utf8(&resp_bytes).map_err(|_| <Error<T>>::HttpFetchingError)?;
-			// Print out our fetched JSON string
-			log::info!("{}", resp_str);
-			// Deserializing JSON to struct, thanks to `serde` and `serde_derive`
-			let json_info = serde_json::from_str(&resp_str).map_err(|_| <Error<T>>::HttpFetchingError)?;
-			Ok(json_info)
+		fn fetch_document(url: &[u8]) -> Result<Vec<u8>, Error<T>> {
+			let url = str::from_utf8(url).map_err(|_| <Error<T>>::InvalidAttestationSource)?;
+			let document = Self::fetch_from_remote(url)?;
+			ensure!(document.len() as u32 <= T::MaxDocumentLen::get(), <Error<T>>::DocumentTooLarge);
+			// the document is attested as is, but it has to be JSON
+			serde_json::from_slice::<serde_json::Value>(&document).map_err(|_| <Error<T>>::InvalidDocument)?;
+			Ok(document)
 		}
 
-		fn fetch_price_info() -> Result<(), Error<T>> {
-			// TODO: 这是你们的功课
-
-			// 利用 offchain worker 取出 DOT 当前对 USD 的价格，并把写到一个 Vec 的存储里，
-			// 你们自己选一种方法提交回链上，并在代码注释为什么用这种方法提交回链上最好。只保留当前最近的 10 个价格，
-			// 其他价格可丢弃 （就是 Vec 的长度长到 10 后，这时再插入一个值时，要先丢弃最早的那个值）。
+		fn submit_document(source: Vec<u8>, document: Vec<u8>, block_number: T::BlockNumber)
+			-> Result<(), Error<T>>
+		{
+			// an unchanged document is not attested again
+			let hash = H256(sp_io::hashing::blake2_256(&document));
+			if Self::attestation(&source).map_or(false, |attestation| attestation.hash == hash) {
+				log::info!("document of {:?} is unchanged", str::from_utf8(&source));
+				return Ok(());
+			}
 
-			// 取得的价格 parse 完后，放在以下存儲：
-			// pub type Prices<T> = StorageValue<_, VecDeque<(u64, Permill)>, ValueQuery>
+			let signer = Signer::<T, T::AuthorityId>::any_account();
+			if let Some((_, res)) = signer.send_unsigned_transaction(
+				|acct| DocumentPayload {
+					source: source.clone(),
+					document: document.clone(),
+					block_number,
+					public: acct.public.clone(),
+				},
+				Call::submit_document_unsigned_with_signed_payload
+			) {
+				return res.map_err(|_| {
+					log::error!("Failed in attesting document by offchain_unsigned_tx_signed_payload");
+					<Error<T>>::DocumentOffchainUnsignedTxSignedPayloadError
+				});
+			}
 
-			// 这个 http 请求可得到当前 DOT 价格：
-			// [https://api.coincap.io/v2/assets/polkadot](https://api.coincap.io/v2/assets/polkadot)。
+			// The case of `None`: no account is available for sending
+			log::error!("No local account available");
+			Err(<Error<T>>::NoLocalAcctForSigning)
+		}
 
-			/// use submit_price_unsigned_with_signed_payload(不签名但具签名信息的交易)
-			/// Consider the situation that http request is not available, and manually updating price is necessary.
-			/// 如果有情况无法正常获取http请求，但需要手动上传价格
-			/// This update as maintenance of the chain should not spend any cost, 
-			/// 	and it is better to record the change by personal signature since this change is made by a person.
-			/// 因为是运营行为，不应该花费币，但人工操作为了保险需要签名
+		/// Fetch the price of every registered asset and submit them on-chain.
+		fn fetch_price_info(block_number: T::BlockNumber) -> Result<(), Error<T>> {
+			// use submit_price_unsigned_with_signed_payload(不签名但具签名信息的交易)
+			// Consider the situation that http request is not available, and manually updating price is necessary.
+			// 如果有情况无法正常获取http请求，但需要手动上传价格
+			// This update as maintenance of the chain should not spend any cost,
+			// 	and it is better to record the change by personal signature since this change is made by a person.
+			// 因为是运营行为，不应该花费币，但人工操作为了保险需要签名
+
+			// A failing asset does not keep the others from being updated.
+			let mut result = Ok(());
+			for (symbol, sources) in PriceSources::<T>::iter() {
+				let round = Self::current_round(&symbol);
+				if let Err(err) = Self::fetch_price_from_sources(&sources)
+					.and_then(|price| Self::submit_price(symbol.clone(), round, price, block_number))
+				{
+					log::error!("fetch price of {:?} error: {:?}", str::from_utf8(&symbol), err);
+					result = Err(err);
+				}
+			}
+			result
+		}
 
-			// local storage reference key
-			let s_info = StorageValueRef::persistent(b"offchain-demo::price-info");
-			let mut lock = StorageLock::<BlockAndTime<Self>>::with_block_and_time_deadline(
-				b"offchain-demo::lock", LOCK_BLOCK_EXPIRATION,
-				rt_offchain::Duration::from_millis(LOCK_TIMEOUT_EXPIRATION)
-			);
-			let mut integer = 0;
-			let mut decimal = Permill::zero();
-			if let Ok(_guard) = lock.try_lock() {
-				match Self::fetch_n_parse_price(HTTP_REMOTE_REQUEST_PRICE) {
-					Ok(price_info) => { 
-						s_info.set(&price_info);
-						(integer, decimal) = Self::price_info_to_price(price_info);
-					}
-					Err(err) => { 
-						return Err(err); 
+		/// Try the sources in order and return the price from the first one that answers.
+		fn fetch_price_from_sources(sources: &[PriceSource]) -> Result<FixedU128, Error<T>> {
+			let mut last_err = <Error<T>>::PriceSourceNotExist;
+			for source in sources {
+				match Self::fetch_price_from_source(source) {
+					Ok(price) => return Ok(price),
+					Err(err) => {
+						log::warn!("price source {:?} failed: {:?}", str::from_utf8(&source.url), err);
+						last_err = err;
 					}
 				}
 			}
+			Err(last_err)
+		}
+
+		fn fetch_price_from_source(source: &PriceSource) -> Result<FixedU128, Error<T>> {
+			let url = str::from_utf8(&source.url).map_err(|_| <Error<T>>::InvalidPriceSource)?;
+			let json_pointer = str::from_utf8(&source.json_pointer).map_err(|_| <Error<T>>::InvalidPriceSource)?;
+			let resp_bytes = Self::fetch_from_remote(url)?;
+			Self::extract_price(&resp_bytes, json_pointer)
+		}
+
+		/// Look up the price field at `json_pointer` of the JSON document `body` and parse it.
+		///   The field can either be a JSON string or a JSON number.
+		pub(crate) fn extract_price(body: &[u8], json_pointer: &str) -> Result<FixedU128, Error<T>> {
+			let document: serde_json::Value = serde_json::from_slice(body)
+			.map_err(|_| <Error<T>>::PriceNotFound)?;
+			let parsed = match document.pointer(json_pointer) {
+				Some(serde_json::Value::String(price)) => ocw_price_parser::parse(price),
+				Some(serde_json::Value::Number(price)) => match (price.as_u64(), price.as_f64()) {
+					(Some(price), _) => ocw_price_parser::parse_number(&price),
+					(None, Some(price)) => ocw_price_parser::parse_number(&price),
+					// only negative integers are left
+					(None, None) => Err(ocw_price_parser::ParseError::Negative),
+				},
+				_ => return Err(<Error<T>>::PriceNotFound),
+			};
+			parsed.map(FixedU128::from_inner).map_err(|e| {
+				log::error!("invalid price at {}: {:?}", json_pointer, e);
+				<Error<T>>::InvalidPrice
+			})
+		}
+
+		fn submit_price(symbol: Vec<u8>, round: u32, price: FixedU128, block_number: T::BlockNumber)
+			-> Result<(), Error<T>>
+		{
 			// Retrieve the signer to sign the payload
 			let signer = Signer::<T, T::AuthorityId>::any_account();
 			// `send_unsigned_transaction` is returning a type of `Option<(Account<T>, Result<(), ()>)>`.
@@ -538,7 +1298,7 @@ pub mod pallet {
 			//   - `Some((account, Ok(())))`: transaction is successfully sent
 			//   - `Some((account, Err(())))`: error occured when sending the transaction
 			if let Some((_, res)) = signer.send_unsigned_transaction(
-				|acct| PricePayload { integer, decimal, public: acct.public.clone() },
+				|acct| PricePayload { symbol: symbol.clone(), round, price, block_number, public: acct.public.clone() },
 				Call::submit_price_unsigned_with_signed_payload
 			) {
 				return res.map_err(|_| {
@@ -586,6 +1346,11 @@ pub mod pallet {
 		}
 
 		fn offchain_unsigned_tx(block_number: T::BlockNumber) -> Result<(), Error<T>> {
+			// The transaction would be rejected by `validate_unsigned` anyway.
+			if Self::next_unsigned_at() > block_number {
+				return Err(<Error<T>>::UnsignedTxTooEarly);
+			}
+
 			let number: u64 = block_number.try_into().unwrap_or(0);
 			let call = Call::submit_number_unsigned(number);
 
@@ -610,7 +1375,7 @@ pub mod pallet {
 			//   - `Some((account, Ok(())))`: transaction is successfully sent
 			//   - `Some((account, Err(())))`: error occured when sending the transaction
 			if let Some((_, res)) = signer.send_unsigned_transaction(
-				|acct| Payload { number, public: acct.public.clone() },
+				|acct| Payload { number, block_number, public: acct.public.clone() },
 				Call::submit_number_unsigned_with_signed_payload
 				) {
 				return res.map_err(|_| {
@@ -623,44 +1388,62 @@ pub mod pallet {
 			log::error!("No local account available");
 			Err(<Error<T>>::NoLocalAcctForSigning)
 		}
+	}
 
-		fn price_info_to_price(price_info: PriceInfo) -> (u64, Permill) {
-			let vec_u8 = price_info.data.price_usd;
-			let price_str = match str::from_utf8(&vec_u8) {
-				Ok(v) => v,
-				Err(e) => {
-					log::info!("Invalid UTF-8 sequence: {}", e);
-					"0.0"
-				},
-			};
-			let mut split = price_str.split(".");
-			(
-				match split.next() {
-					Some(v) => {
-						match v.parse::<u64>() {
-							Ok(v_u64) => v_u64,
-							Err(e) => {
-								log::info!("Invalid u64 str: {}", e);
-								0
-							},
-						}
-					},
-					None => 0,
-				},
-				match split.next() {
-					Some(v) => {
-						match v[..6].parse::<u32>() {
-							Ok(v_u32) => Permill::from_parts(v_u32),
-							Err(e) => {
-								log::info!("Invalid Permill str: {}", e);
-								Permill::zero()
-							}
-						}
-					},
-					None => Permill::zero(),
-				},	
+	/// The statistics of the `window` blocks up to the last observation. A window reaching
+	///   before the oldest observation starts at it.
+	fn window_stats<BlockNumber: AtLeast32BitUnsigned + Copy>(
+		observations: &VecDeque<Observation<BlockNumber>>,
+		window: BlockNumber,
+	) -> Option<PriceStats<BlockNumber>> {
+		let last = observations.back()?;
+		// the observation whose price is in effect at the start of the window
+		let start = last.block_number.saturating_sub(window);
+		let first_index = observations.iter().rposition(|o| o.block_number <= start).unwrap_or(0);
+		let first = &observations[first_index];
+		let start = start.max(first.block_number);
+
+		let (min, max) = observations
+			.iter()
+			.skip(first_index)
+			.fold((first.price, first.price), |(min, max), o| (min.min(o.price), max.max(o.price)));
+
+		// `first` was the latest price from `first.block_number` to `start`
+		let duration: u128 = last.block_number.saturating_sub(start).saturated_into();
+		let twap = if duration == 0 {
+			last.price
+		} else {
+			let before_start: u128 = start.saturating_sub(first.block_number).saturated_into();
+			let at_start = first.price_cumulative.into_inner()
+				.saturating_add(first.price.into_inner().saturating_mul(before_start));
+			FixedU128::from_inner(
+				last.price_cumulative.into_inner().saturating_sub(at_start) / duration,
 			)
-		}
+		};
+
+		let changes = last.changes.saturating_sub(first.changes);
+		let volatility = if changes == 0 {
+			Zero::zero()
+		} else {
+			let squared_changes = last.squared_change_cumulative
+				.saturating_sub(first.squared_change_cumulative);
+			sqrt(FixedU128::from_inner(squared_changes.into_inner() / changes as u128))
+		};
+
+		Some(PriceStats { twap, min, max, volatility, block_number: last.block_number })
+	}
+
+	/// `|price - previous| / previous`, zero when `previous` is zero.
+	fn relative_change(previous: FixedU128, price: FixedU128) -> FixedU128 {
+		let (previous, price) = (previous.into_inner(), price.into_inner());
+		FixedU128::checked_from_rational(price.max(previous) - price.min(previous), previous)
+			.unwrap_or_else(Zero::zero)
+	}
+
+	fn sqrt(value: FixedU128) -> FixedU128 {
+		// sqrt(inner / DIV) * DIV == sqrt(inner * DIV), which fits in 256 bits
+		let root = (U256::from(value.into_inner()) * U256::from(FixedU128::DIV)).integer_sqrt();
+		FixedU128::from_inner(root.low_u128())
 	}
 
 	impl<T: Config> BlockNumberProvider for Pallet<T> {
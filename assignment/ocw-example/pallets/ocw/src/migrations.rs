@@ -0,0 +1,23 @@
+//! Storage migrations of the ocw pallet.
+
+use crate::{Config, Pallet, Prices};
+use frame_support::{
+	storage::{unhashed, StoragePrefixedMap},
+	traits::{Get, StorageVersion},
+	weights::Weight,
+};
+
+/// Version 0 kept the last prices of DOT in a single `Prices` value, version 1 keeps the
+/// aggregated prices of each asset symbol in the `Prices` map.
+pub mod v1 {
+	use super::*;
+
+	/// Remove the version 0 value, which lives at the prefix of the map and cannot be decoded as
+	/// one of its entries. The prices of the registered sources are aggregated again from then on.
+	pub fn migrate<T: Config>() -> Weight {
+		unhashed::kill(&Prices::<T>::final_prefix());
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().writes(2)
+	}
+}
@@ -0,0 +1,1081 @@
+use crate::{
+	mock::*, AggregatedPrice, Attestation, DocumentPayload, Error, OffchainTask, PricePayload,
+	PriceSource, PriceSources, PriceStats, Prices, Submissions, TaskSchedule, TaskStatus,
+};
+use frame_support::{
+	assert_noop, assert_ok,
+	dispatch::Dispatchable,
+	storage::{unhashed, StoragePrefixedMap},
+	traits::{GenesisBuild, Hooks, StorageVersion},
+	unsigned::ValidateUnsigned,
+};
+use parity_scale_codec::{Decode, Encode};
+use parking_lot::RwLock;
+use sp_arithmetic::{FixedPointNumber, FixedU128, Permill};
+use sp_core::{
+	offchain::{
+		testing::{self, OffchainState, PoolState},
+		OffchainDbExt, OffchainStorage, OffchainWorkerExt, TransactionPoolExt,
+	},
+	sr25519::{Public, Signature},
+	H256,
+};
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+use sp_runtime::{
+	offchain::{
+		storage::StorageValueRef,
+		storage_lock::{BlockAndTime, StorageLock},
+		Duration,
+	},
+	traits::BadOrigin,
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidity},
+	RuntimeAppPublic,
+};
+use std::{collections::VecDeque, sync::Arc};
+
+const PHRASE: &str = "news slush supreme milk chapter athlete soap sausage put clutch what kitten";
+const DOT_URL: &[u8] = b"https://api.coincap.io/v2/assets/polkadot";
+const DOT_BACKUP_URL: &[u8] = b"https://example.com/price/dot";
+const KSM_URL: &[u8] = b"https://example.com/price/ksm";
+const GITHUB_URL: &[u8] = b"https://api.github.com/orgs/substrate-developer-hub";
+const GITHUB_ORG: &[u8] = br#"{"login":"substrate-developer-hub","public_repos":40}"#;
+
+fn int(n: u64) -> FixedU128 {
+	FixedU128::saturating_from_integer(n)
+}
+
+fn price(text: &str) -> FixedU128 {
+	FixedU128::from_inner(ocw_price_parser::parse(text).unwrap())
+}
+
+fn reporter(n: u8) -> Public {
+	Public::from_raw([n; 32])
+}
+
+// test externalities with offchain extensions and the account of the local offchain worker key
+fn offchain_ext(
+) -> (sp_io::TestExternalities, Arc<RwLock<OffchainState>>, Arc<RwLock<PoolState>>, Public) {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	let local = SyncCryptoStore::sr25519_generate_new(
+		&keystore,
+		crate::crypto::Public::ID,
+		Some(&format!("{}/hunter1", PHRASE)),
+	)
+	.unwrap();
+
+	let mut t = new_test_ext();
+	t.register_extension(OffchainDbExt::new(offchain.clone()));
+	t.register_extension(OffchainWorkerExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+	t.register_extension(KeystoreExt(Arc::new(keystore)));
+	(t, offchain_state, pool_state, local)
+}
+
+fn expect_get(state: &Arc<RwLock<OffchainState>>, url: &[u8], body: &[u8]) {
+	state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: String::from_utf8(url.to_vec()).unwrap(),
+		headers: vec![("User-Agent".into(), "jimmychu0807".into())],
+		response: Some(body.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+// validate and dispatch every transaction the offchain worker put into the pool
+fn dispatch_pool(pool_state: &Arc<RwLock<PoolState>>) -> usize {
+	let txs: Vec<_> = pool_state.write().transactions.drain(..).collect();
+	for tx in txs.iter() {
+		let tx = Extrinsic::decode(&mut &tx[..]).unwrap();
+		assert_eq!(tx.signature, None);
+		if let Call::OcwDemo(ref inner) = tx.call {
+			assert_ok!(OcwDemo::validate_unsigned(TransactionSource::Local, inner));
+		}
+		assert_ok!(tx.call.dispatch(Origin::none()));
+	}
+	txs.len()
+}
+
+// run the offchain worker on top of block `n`
+fn run_worker(n: u64) {
+	System::set_block_number(n);
+	OcwDemo::offchain_worker(n);
+}
+
+fn add_source(symbol: &[u8], url: &[u8], json_pointer: &[u8]) {
+	assert_ok!(OcwDemo::add_price_source(
+		Origin::root(),
+		symbol.to_vec(),
+		url.to_vec(),
+		json_pointer.to_vec()
+	));
+}
+
+fn add_reporter(who: Public) {
+	assert_ok!(OcwDemo::add_reporter(Origin::root(), who));
+}
+
+// submit a price as `who`, the signature is only checked when validating the transaction
+fn submit(
+	symbol: &[u8],
+	round: u32,
+	who: Public,
+	integer: u64,
+) -> frame_support::dispatch::DispatchResult {
+	OcwDemo::submit_price_unsigned_with_signed_payload(
+		Origin::none(),
+		PricePayload {
+			symbol: symbol.to_vec(),
+			round,
+			price: int(integer),
+			block_number: System::block_number(),
+			public: who,
+		},
+		Signature::from_raw([0u8; 64]),
+	)
+}
+
+#[test]
+fn add_price_source_works() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_source(b"DOT", DOT_BACKUP_URL, b"");
+
+		assert_eq!(
+			OcwDemo::price_sources(b"DOT".to_vec()),
+			vec![
+				PriceSource { url: DOT_URL.to_vec(), json_pointer: b"/data/priceUsd".to_vec() },
+				PriceSource { url: DOT_BACKUP_URL.to_vec(), json_pointer: vec![] },
+			]
+		);
+	});
+}
+
+#[test]
+fn add_price_source_fails() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			OcwDemo::add_price_source(Origin::none(), b"DOT".to_vec(), DOT_URL.to_vec(), vec![]),
+			BadOrigin
+		);
+		// not a http url
+		assert_noop!(
+			OcwDemo::add_price_source(Origin::root(), b"DOT".to_vec(), b"ftp://a".to_vec(), vec![]),
+			Error::<Test>::InvalidPriceSource
+		);
+		// a JSON pointer starts with '/'
+		assert_noop!(
+			OcwDemo::add_price_source(
+				Origin::root(),
+				b"DOT".to_vec(),
+				DOT_URL.to_vec(),
+				b"data".to_vec()
+			),
+			Error::<Test>::InvalidPriceSource
+		);
+		assert_noop!(
+			OcwDemo::add_price_source(Origin::root(), vec![], DOT_URL.to_vec(), vec![]),
+			Error::<Test>::InvalidPriceSource
+		);
+
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		assert_noop!(
+			OcwDemo::add_price_source(
+				Origin::root(),
+				b"DOT".to_vec(),
+				DOT_URL.to_vec(),
+				b"/data/priceUsd".to_vec()
+			),
+			Error::<Test>::DuplicatePriceSource
+		);
+
+		add_source(b"DOT", DOT_BACKUP_URL, b"");
+		assert_noop!(
+			OcwDemo::add_price_source(Origin::root(), b"DOT".to_vec(), KSM_URL.to_vec(), vec![]),
+			Error::<Test>::TooManyPriceSources
+		);
+	});
+}
+
+#[test]
+fn remove_price_source_works() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_source(b"DOT", DOT_BACKUP_URL, b"");
+
+		assert_noop!(OcwDemo::remove_price_source(Origin::none(), b"DOT".to_vec(), 0), BadOrigin);
+		assert_noop!(
+			OcwDemo::remove_price_source(Origin::root(), b"DOT".to_vec(), 2),
+			Error::<Test>::PriceSourceNotExist
+		);
+		assert_noop!(
+			OcwDemo::remove_price_source(Origin::root(), b"KSM".to_vec(), 0),
+			Error::<Test>::PriceSourceNotExist
+		);
+
+		assert_ok!(OcwDemo::remove_price_source(Origin::root(), b"DOT".to_vec(), 0));
+		assert_eq!(
+			OcwDemo::price_sources(b"DOT".to_vec()),
+			vec![PriceSource { url: DOT_BACKUP_URL.to_vec(), json_pointer: vec![] }]
+		);
+
+		assert_ok!(OcwDemo::remove_price_source(Origin::root(), b"DOT".to_vec(), 0));
+		assert!(!PriceSources::<Test>::contains_key(b"DOT".to_vec()));
+	});
+}
+
+#[test]
+fn genesis_config_registers_price_sources() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	let config = crate::GenesisConfig::<Test> {
+		price_sources: vec![(b"DOT".to_vec(), DOT_URL.to_vec(), b"/data/priceUsd".to_vec())],
+		reporters: vec![reporter(1)],
+		attestation_sources: vec![(b"github-org".to_vec(), GITHUB_URL.to_vec())],
+	};
+	GenesisBuild::<Test>::assimilate_storage(&config, &mut t).unwrap();
+
+	sp_io::TestExternalities::from(t).execute_with(|| {
+		assert_eq!(
+			OcwDemo::price_sources(b"DOT".to_vec()),
+			vec![PriceSource { url: DOT_URL.to_vec(), json_pointer: b"/data/priceUsd".to_vec() }]
+		);
+		assert_eq!(OcwDemo::reporters(), vec![reporter(1)]);
+		assert_eq!(OcwDemo::attestation_source(b"github-org".to_vec()), Some(GITHUB_URL.to_vec()));
+	});
+}
+
+#[test]
+fn extract_price_follows_json_pointer() {
+	let body =
+		br#"{"data":{"priceUsd":"31.1234567890","history":[{"price":30.5123456},{"price":7}]}}"#;
+	assert_eq!(OcwDemo::extract_price(body, "/data/priceUsd").ok(), Some(price("31.123456789")));
+	// numbers are accepted as well
+	assert_eq!(
+		OcwDemo::extract_price(body, "/data/history/0/price").ok(),
+		Some(price("30.5123456"))
+	);
+	assert_eq!(OcwDemo::extract_price(body, "/data/history/1/price").ok(), Some(int(7)));
+	assert!(matches!(
+		OcwDemo::extract_price(body, "/data/price"),
+		Err(Error::<Test>::PriceNotFound)
+	));
+	// the pointer must lead to a single value
+	assert!(matches!(OcwDemo::extract_price(body, "/data"), Err(Error::<Test>::PriceNotFound)));
+	assert!(matches!(OcwDemo::extract_price(b"<html>", ""), Err(Error::<Test>::PriceNotFound)));
+}
+
+#[test]
+fn extract_price_rejects_invalid_prices() {
+	for body in [
+		&br#"{"p":"n/a"}"#[..],
+		br#"{"p":"-3.5"}"#,
+		br#"{"p":-3}"#,
+		br#"{"p":""}"#,
+		br#"{"p":"1e40"}"#,
+	] {
+		assert!(matches!(OcwDemo::extract_price(body, "/p"), Err(Error::<Test>::InvalidPrice)));
+	}
+	// many fractional digits and exponents are fine
+	assert_eq!(
+		OcwDemo::extract_price(br#"{"p":"0.5"}"#, "/p").ok(),
+		Some(FixedU128::saturating_from_rational(1, 2))
+	);
+	assert_eq!(OcwDemo::extract_price(br#"{"p":"2.5e3"}"#, "/p").ok(), Some(int(2500)));
+	assert_eq!(OcwDemo::extract_price(br#"{"p":1.25e-2}"#, "/p").ok(), Some(price("0.0125")));
+}
+
+#[test]
+fn manage_reporters_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(OcwDemo::add_reporter(Origin::none(), reporter(1)), BadOrigin);
+		for n in 1..=4 {
+			add_reporter(reporter(n));
+		}
+		assert_noop!(
+			OcwDemo::add_reporter(Origin::root(), reporter(1)),
+			Error::<Test>::AlreadyReporter
+		);
+		assert_noop!(
+			OcwDemo::add_reporter(Origin::root(), reporter(5)),
+			Error::<Test>::TooManyReporters
+		);
+
+		assert_noop!(OcwDemo::remove_reporter(Origin::none(), reporter(1)), BadOrigin);
+		assert_ok!(OcwDemo::remove_reporter(Origin::root(), reporter(1)));
+		assert_noop!(
+			OcwDemo::remove_reporter(Origin::root(), reporter(1)),
+			Error::<Test>::NotReporter
+		);
+		assert!(!OcwDemo::reporters().contains(&reporter(1)));
+	});
+}
+
+#[test]
+fn submit_price_checks_reporter_and_round() {
+	new_test_ext().execute_with(|| {
+		add_reporter(reporter(1));
+		assert_noop!(submit(b"DOT", 0, reporter(1), 30), Error::<Test>::PriceSourceNotExist);
+
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		assert_noop!(submit(b"DOT", 0, reporter(2), 30), Error::<Test>::NotReporter);
+		assert_noop!(submit(b"DOT", 1, reporter(1), 30), Error::<Test>::WrongRound);
+
+		assert_ok!(submit(b"DOT", 0, reporter(1), 30));
+		assert_eq!(OcwDemo::submissions(b"DOT".to_vec(), reporter(1)), Some(int(30)));
+		assert_noop!(submit(b"DOT", 0, reporter(1), 31), Error::<Test>::AlreadySubmitted);
+	});
+}
+
+#[test]
+fn round_finalizes_to_median_at_quorum() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		for n in 1..=4 {
+			add_reporter(reporter(n));
+		}
+
+		assert_ok!(submit(b"DOT", 0, reporter(1), 30));
+		assert_ok!(submit(b"DOT", 0, reporter(2), 50));
+		assert_eq!(OcwDemo::aggregated_price(b"DOT".to_vec()), None);
+
+		// the third submission reaches the quorum
+		assert_ok!(submit(b"DOT", 0, reporter(3), 31));
+		assert_eq!(
+			OcwDemo::aggregated_price(b"DOT".to_vec()),
+			Some(AggregatedPrice { price: int(31), round: 0, block_number: 0 })
+		);
+		assert_eq!(OcwDemo::prices(b"DOT".to_vec()), vec![int(31)]);
+		assert_eq!(OcwDemo::current_round(b"DOT".to_vec()), 1);
+		assert_eq!(Submissions::<Test>::iter_prefix(b"DOT".to_vec()).count(), 0);
+
+		// 30 is within 10% of 31, 50 is not
+		assert_eq!(OcwDemo::deviations(reporter(1)), 0);
+		assert_eq!(OcwDemo::deviations(reporter(2)), 1);
+		assert_eq!(OcwDemo::deviations(reporter(3)), 0);
+
+		// a late submission for the finalized round is rejected
+		assert_noop!(submit(b"DOT", 0, reporter(4), 31), Error::<Test>::WrongRound);
+	});
+}
+
+#[test]
+fn median_of_even_submissions_is_the_mean_of_the_middle_two() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		for n in 1..=4 {
+			add_reporter(reporter(n));
+		}
+
+		assert_ok!(submit(b"DOT", 0, reporter(1), 30));
+		assert_ok!(submit(b"DOT", 0, reporter(2), 31));
+		// reporter 2 is removed, its submission no longer counts towards the quorum
+		assert_ok!(OcwDemo::remove_reporter(Origin::root(), reporter(2)));
+		assert_ok!(submit(b"DOT", 0, reporter(3), 32));
+		assert_eq!(OcwDemo::current_round(b"DOT".to_vec()), 0);
+
+		// once added back it counts again, four submissions are aggregated
+		add_reporter(reporter(2));
+		assert_ok!(submit(b"DOT", 0, reporter(4), 33));
+		let price = OcwDemo::aggregated_price(b"DOT".to_vec()).unwrap();
+		assert_eq!(price.price, FixedU128::saturating_from_rational(63, 2));
+
+		// a new round starts over and only needs three submissions
+		assert_ok!(submit(b"DOT", 1, reporter(1), 40));
+		assert_ok!(submit(b"DOT", 1, reporter(3), 41));
+		assert_ok!(submit(b"DOT", 1, reporter(4), 40));
+		let price = OcwDemo::aggregated_price(b"DOT".to_vec()).unwrap();
+		assert_eq!((price.price, price.round), (int(40), 1));
+		assert_noop!(submit(b"DOT", 1, reporter(2), 41), Error::<Test>::WrongRound);
+	});
+}
+
+#[test]
+fn prices_keep_the_latest_rounds() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_source(b"KSM", KSM_URL, b"/price");
+		for n in 1..=3 {
+			add_reporter(reporter(n));
+		}
+
+		for round in 0..12 {
+			for n in 1..=3 {
+				assert_ok!(submit(b"DOT", round, reporter(n), round as u64));
+			}
+		}
+
+		// only the latest 10 prices are kept
+		let prices: Vec<_> = OcwDemo::prices(b"DOT".to_vec()).into_iter().collect();
+		assert_eq!(prices, (2..12).map(int).collect::<Vec<_>>());
+		assert!(Prices::<Test>::get(b"KSM".to_vec()).is_empty());
+	});
+}
+
+// aggregate `price` as the price of DOT in block `n`
+fn aggregate(n: u64, round: u32, price: FixedU128) {
+	System::set_block_number(n);
+	for who in 1..=3 {
+		assert_ok!(OcwDemo::submit_price_unsigned_with_signed_payload(
+			Origin::none(),
+			PricePayload {
+				symbol: b"DOT".to_vec(),
+				round,
+				price,
+				block_number: n,
+				public: reporter(who),
+			},
+			Signature::from_raw([0u8; 64]),
+		));
+	}
+	assert_eq!(OcwDemo::aggregated_price(b"DOT".to_vec()).unwrap().round, round);
+}
+
+fn stats(window: u64) -> PriceStats<u64> {
+	OcwDemo::price_statistics(b"DOT".to_vec(), window).unwrap()
+}
+
+#[test]
+fn price_stats_follow_aggregated_prices() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		for n in 1..=3 {
+			add_reporter(reporter(n));
+		}
+		assert!(OcwDemo::price_stats(b"DOT").is_empty());
+
+		aggregate(10, 0, int(100));
+		let first = PriceStats {
+			twap: int(100),
+			min: int(100),
+			max: int(100),
+			volatility: int(0),
+			block_number: 10,
+		};
+		assert_eq!(OcwDemo::price_stats(b"DOT"), vec![(10, first.clone()), (100, first)]);
+
+		// every price changes by 10% from the previous one
+		aggregate(14, 1, int(110));
+		let volatility = price("0.1");
+		assert_eq!(
+			stats(10),
+			PriceStats {
+				twap: int(100),
+				min: int(100),
+				max: int(110),
+				volatility,
+				block_number: 14
+			}
+		);
+
+		aggregate(20, 2, int(99));
+		// 100 for 4 blocks and 110 for 6 blocks
+		assert_eq!(
+			stats(10),
+			PriceStats {
+				twap: int(106),
+				min: int(99),
+				max: int(110),
+				volatility,
+				block_number: 20
+			}
+		);
+
+		aggregate(24, 3, price("108.9"));
+		// the window starts in block 14: 110 for 6 blocks and 99 for 4 blocks
+		assert_eq!(
+			stats(10),
+			PriceStats {
+				twap: price("105.6"),
+				min: int(99),
+				max: int(110),
+				volatility,
+				block_number: 24,
+			}
+		);
+		assert_eq!(
+			stats(100),
+			PriceStats {
+				twap: int(104),
+				min: int(99),
+				max: int(110),
+				volatility,
+				block_number: 24
+			}
+		);
+		assert!(OcwDemo::price_statistics(b"KSM".to_vec(), 10).is_none());
+	});
+}
+
+#[test]
+fn price_stats_only_cover_the_kept_observations() {
+	new_test_ext().execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		for n in 1..=3 {
+			add_reporter(reporter(n));
+		}
+
+		let prices = [int(100), int(110), int(99), price("108.9"), price("98.01")];
+		for (round, (n, price)) in [10, 14, 20, 24, 30].iter().zip(prices.iter()).enumerate() {
+			aggregate(*n, round as u32, *price);
+		}
+
+		// the price of block 10 is dropped, the window of 100 blocks starts in block 14
+		let observations = OcwDemo::observations(b"DOT".to_vec());
+		assert_eq!(observations.len(), 4);
+		assert_eq!(observations.front().unwrap().block_number, 14);
+		assert_eq!(
+			stats(100),
+			PriceStats {
+				twap: price("106.8375"),
+				min: price("98.01"),
+				max: int(110),
+				volatility: price("0.1"),
+				block_number: 30,
+			}
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_falls_back_to_next_source() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_source(b"DOT", DOT_BACKUP_URL, b"/usd");
+		add_reporter(local);
+
+		// the first source answers something unexpected
+		expect_get(&state, DOT_URL, br#"{"error":"rate limited"}"#);
+		expect_get(&state, DOT_BACKUP_URL, br#"{"usd":"31.1234567890"}"#);
+		run_worker(4);
+
+		assert_eq!(dispatch_pool(&pool_state), 1);
+		assert_eq!(OcwDemo::submissions(b"DOT".to_vec(), local), Some(price("31.123456789")));
+	});
+}
+
+#[test]
+fn offchain_worker_does_not_submit_invalid_prices() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(local);
+
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"unavailable"}}"#);
+		run_worker(4);
+
+		// nothing is submitted rather than a zero price
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn offchain_worker_fetches_every_asset() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_source(b"KSM", KSM_URL, b"/price");
+		add_reporter(local);
+
+		// assets are visited in storage order
+		for symbol in PriceSources::<Test>::iter_keys() {
+			if symbol == b"DOT".to_vec() {
+				expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"31.1234567890"}}"#);
+			} else {
+				expect_get(&state, KSM_URL, br#"{"price":250.654321}"#);
+			}
+		}
+		run_worker(4);
+
+		assert_eq!(dispatch_pool(&pool_state), 2);
+		assert_eq!(OcwDemo::submissions(b"DOT".to_vec(), local), Some(price("31.123456789")));
+		assert_eq!(OcwDemo::submissions(b"KSM".to_vec(), local), Some(price("250.654321")));
+	});
+}
+
+#[test]
+fn offchain_worker_reports_the_current_round() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(reporter(1));
+		add_reporter(reporter(2));
+		add_reporter(local);
+		crate::CurrentRound::<Test>::insert(b"DOT".to_vec(), 7);
+		assert_ok!(submit(b"DOT", 7, reporter(1), 30));
+		assert_ok!(submit(b"DOT", 7, reporter(2), 32));
+
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"31.1234567890"}}"#);
+		run_worker(4);
+
+		// the offchain worker completes the quorum
+		assert_eq!(dispatch_pool(&pool_state), 1);
+		let aggregated = OcwDemo::aggregated_price(b"DOT".to_vec()).unwrap();
+		assert_eq!((aggregated.price, aggregated.round), (price("31.123456789"), 7));
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_unauthorized_prices() {
+	let (mut t, state, pool_state, _) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+
+		// the local key is not a reporter
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"31.1234567890"}}"#);
+		run_worker(4);
+
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = Extrinsic::decode(&mut &tx[..]).unwrap();
+		if let Call::OcwDemo(inner) = tx.call {
+			assert!(OcwDemo::validate_unsigned(TransactionSource::External, &inner).is_err());
+		} else {
+			panic!("unexpected call");
+		}
+	});
+}
+
+fn schedule(interval: u64, offset: u64, enabled: bool) -> TaskSchedule<u64> {
+	TaskSchedule { interval, offset, enabled }
+}
+
+fn task_status(task: OffchainTask) -> Option<TaskStatus<u64>> {
+	StorageValueRef::persistent(&task.status_key()).get().unwrap()
+}
+
+#[test]
+fn tasks_run_every_fifth_block_by_default() {
+	new_test_ext().execute_with(|| {
+		for (offset, task) in OffchainTask::ALL.iter().enumerate() {
+			assert_eq!(OcwDemo::task_schedule(*task), schedule(5, offset as u64, true));
+		}
+		let price_info = OcwDemo::task_schedule(OffchainTask::PriceInfo);
+		assert!(price_info.is_due(4) && price_info.is_due(9));
+		assert!(!price_info.is_due(5));
+	});
+}
+
+#[test]
+fn set_task_schedule_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			OcwDemo::set_task_schedule(
+				Origin::signed(reporter(1)),
+				OffchainTask::PriceInfo,
+				schedule(3, 1, true)
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			OcwDemo::set_task_schedule(
+				Origin::root(),
+				OffchainTask::PriceInfo,
+				schedule(0, 0, true)
+			),
+			Error::<Test>::InvalidSchedule
+		);
+		assert_noop!(
+			OcwDemo::set_task_schedule(
+				Origin::root(),
+				OffchainTask::PriceInfo,
+				schedule(3, 3, true)
+			),
+			Error::<Test>::InvalidSchedule
+		);
+
+		assert_ok!(OcwDemo::set_task_schedule(
+			Origin::root(),
+			OffchainTask::PriceInfo,
+			schedule(3, 1, true)
+		));
+		assert_eq!(OcwDemo::task_schedule(OffchainTask::PriceInfo), schedule(3, 1, true));
+		// the other tasks keep their schedule
+		assert_eq!(OcwDemo::task_schedule(OffchainTask::Attestation), schedule(5, 3, true));
+	});
+}
+
+#[test]
+fn offchain_worker_follows_task_schedule() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(local);
+		assert_ok!(OcwDemo::set_task_schedule(
+			Origin::root(),
+			OffchainTask::PriceInfo,
+			schedule(3, 1, true)
+		));
+
+		// no task is due in block 9
+		run_worker(9);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(task_status(OffchainTask::PriceInfo), None);
+
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"31.1234567890"}}"#);
+		run_worker(4);
+		assert_eq!(dispatch_pool(&pool_state), 1);
+
+		// disabled tasks never run
+		assert_ok!(OcwDemo::set_task_schedule(
+			Origin::root(),
+			OffchainTask::PriceInfo,
+			schedule(3, 1, false)
+		));
+		// block 19 would be due for both the new and the default schedule
+		run_worker(19);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn offchain_worker_records_task_status() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(local);
+
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"31.1234567890"}}"#);
+		run_worker(4);
+		assert_eq!(dispatch_pool(&pool_state), 1);
+		assert_eq!(
+			task_status(OffchainTask::PriceInfo),
+			Some(TaskStatus { last_run: Some(4), last_error: None })
+		);
+
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"unavailable"}}"#);
+		run_worker(9);
+		let failed =
+			TaskStatus { last_run: Some(9), last_error: Some((9, b"InvalidPrice".to_vec())) };
+		assert_eq!(task_status(OffchainTask::PriceInfo), Some(failed));
+
+		// the last error is kept after a successful run
+		expect_get(&state, DOT_URL, br#"{"data":{"priceUsd":"31.1234567890"}}"#);
+		run_worker(14);
+		let recovered =
+			TaskStatus { last_run: Some(14), last_error: Some((9, b"InvalidPrice".to_vec())) };
+		assert_eq!(task_status(OffchainTask::PriceInfo), Some(recovered));
+	});
+}
+
+#[test]
+fn tasks_have_independent_locks() {
+	let (mut t, _, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(local);
+
+		// a previous run of the price task is still going on
+		let lock_key = OffchainTask::PriceInfo.lock_key();
+		let mut lock = StorageLock::<BlockAndTime<OcwDemo>>::with_block_and_time_deadline(
+			&lock_key,
+			100,
+			Duration::from_millis(100_000),
+		);
+		lock.try_lock().unwrap().forget();
+
+		run_worker(4);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(task_status(OffchainTask::PriceInfo), None);
+
+		// the other tasks are not held back
+		run_worker(6);
+		assert_eq!(pool_state.read().transactions.len(), 1);
+		assert_eq!(
+			task_status(OffchainTask::UnsignedTx),
+			Some(TaskStatus { last_run: Some(6), last_error: None })
+		);
+	});
+}
+
+// a price payload signed by `who`, whose key must be in the keystore
+fn signed_price(who: Public, round: u32, integer: u64, block_number: u64) -> crate::Call<Test> {
+	let payload = PricePayload {
+		symbol: b"DOT".to_vec(),
+		round,
+		price: int(integer),
+		block_number,
+		public: who,
+	};
+	let signature = sp_io::crypto::sr25519_sign(crate::KEY_TYPE, &who, &payload.encode()).unwrap();
+	crate::Call::submit_price_unsigned_with_signed_payload(payload, signature)
+}
+
+fn validate(call: &crate::Call<Test>) -> TransactionValidity {
+	OcwDemo::validate_unsigned(TransactionSource::External, call)
+}
+
+#[test]
+fn validate_unsigned_rejects_stale_and_future_payloads() {
+	let (mut t, _, _, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(local);
+		System::set_block_number(10);
+
+		assert_eq!(validate(&signed_price(local, 0, 30, 11)), InvalidTransaction::Future.into());
+		// payloads are valid for 5 blocks
+		assert_eq!(validate(&signed_price(local, 0, 30, 5)), InvalidTransaction::Stale.into());
+		assert_eq!(validate(&signed_price(local, 0, 30, 6)).unwrap().longevity, 1);
+		assert_eq!(validate(&signed_price(local, 0, 30, 10)).unwrap().longevity, 5);
+
+		// the block number is signed as well
+		let call = match signed_price(local, 0, 30, 5) {
+			crate::Call::submit_price_unsigned_with_signed_payload(mut payload, signature) => {
+				payload.block_number = 10;
+				crate::Call::submit_price_unsigned_with_signed_payload(payload, signature)
+			},
+			_ => unreachable!(),
+		};
+		assert_eq!(validate(&call), InvalidTransaction::BadProof.into());
+	});
+}
+
+#[test]
+fn replayed_price_submissions_are_rejected() {
+	let (mut t, _, _, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		add_reporter(reporter(1));
+		add_reporter(reporter(2));
+		add_reporter(local);
+		System::set_block_number(10);
+
+		let call = signed_price(local, 0, 30, 10);
+		assert_ok!(validate(&call));
+		assert_ok!(Call::OcwDemo(call.clone()).dispatch(Origin::none()));
+		assert_eq!(validate(&call), InvalidTransaction::Stale.into());
+
+		// the round is over, the payload stays rejected
+		assert_ok!(submit(b"DOT", 0, reporter(1), 31));
+		assert_ok!(submit(b"DOT", 0, reporter(2), 32));
+		assert_eq!(OcwDemo::current_round(b"DOT".to_vec()), 1);
+		System::set_block_number(11);
+		assert_eq!(validate(&call), InvalidTransaction::Stale.into());
+
+		// the reporter waits 3 blocks before its next price of the asset
+		assert_eq!(OcwDemo::next_signed_payload_at(local, Some(b"DOT".to_vec())), 13);
+		assert_eq!(validate(&signed_price(local, 1, 30, 11)), InvalidTransaction::Stale.into());
+		System::set_block_number(13);
+		assert_ok!(validate(&signed_price(local, 1, 30, 13)));
+	});
+}
+
+#[test]
+fn price_priority_grows_with_staleness() {
+	let (mut t, _, _, local) = offchain_ext();
+	t.execute_with(|| {
+		add_source(b"DOT", DOT_URL, b"/data/priceUsd");
+		for n in 1..4 {
+			add_reporter(reporter(n));
+		}
+		add_reporter(local);
+		System::set_block_number(10);
+
+		// no price has ever been aggregated
+		assert_eq!(validate(&signed_price(local, 0, 30, 10)).unwrap().priority, 110);
+
+		for n in 1..4 {
+			assert_ok!(submit(b"DOT", 0, reporter(n), 30));
+		}
+		assert_eq!(OcwDemo::aggregated_price(b"DOT".to_vec()).unwrap().block_number, 10);
+
+		System::set_block_number(14);
+		assert_eq!(validate(&signed_price(local, 1, 30, 14)).unwrap().priority, 104);
+	});
+}
+
+#[test]
+fn unsigned_numbers_are_rate_limited() {
+	let (mut t, _, pool_state, _) = offchain_ext();
+	t.execute_with(|| {
+		System::set_block_number(10);
+		let call = crate::Call::submit_number_unsigned(10);
+		assert_ok!(validate(&call));
+		assert_ok!(Call::OcwDemo(call.clone()).dispatch(Origin::none()));
+		assert_eq!(validate(&call), InvalidTransaction::Stale.into());
+
+		// the offchain worker does not send a transaction bound to be rejected
+		run_worker(11);
+		assert!(pool_state.read().transactions.is_empty());
+		let too_early = TaskStatus {
+			last_run: Some(11),
+			last_error: Some((11, b"UnsignedTxTooEarly".to_vec())),
+		};
+		assert_eq!(task_status(OffchainTask::UnsignedTx), Some(too_early));
+
+		System::set_block_number(13);
+		assert_ok!(validate(&call));
+	});
+}
+
+fn set_attestation_source(source: &[u8], url: &[u8]) {
+	assert_ok!(OcwDemo::set_attestation_source(
+		Origin::root(),
+		source.to_vec(),
+		Some(url.to_vec())
+	));
+}
+
+fn signed_document(who: Public, document: &[u8], block_number: u64) -> crate::Call<Test> {
+	let payload = DocumentPayload {
+		source: b"github-org".to_vec(),
+		document: document.to_vec(),
+		block_number,
+		public: who,
+	};
+	let signature = sp_io::crypto::sr25519_sign(crate::KEY_TYPE, &who, &payload.encode()).unwrap();
+	crate::Call::submit_document_unsigned_with_signed_payload(payload, signature)
+}
+
+fn hash(document: &[u8]) -> H256 {
+	H256(sp_io::hashing::blake2_256(document))
+}
+
+#[test]
+fn set_attestation_source_works() {
+	new_test_ext().execute_with(|| {
+		// events are not recorded in the genesis block
+		System::set_block_number(1);
+		assert_noop!(
+			OcwDemo::set_attestation_source(
+				Origin::signed(reporter(1)),
+				b"github-org".to_vec(),
+				Some(GITHUB_URL.to_vec())
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			OcwDemo::set_attestation_source(
+				Origin::root(),
+				b"github-org".to_vec(),
+				Some(b"ftp://example.com".to_vec())
+			),
+			Error::<Test>::InvalidAttestationSource
+		);
+		assert_noop!(
+			OcwDemo::set_attestation_source(Origin::root(), b"github-org".to_vec(), None),
+			Error::<Test>::AttestationSourceNotExist
+		);
+
+		set_attestation_source(b"github-org", GITHUB_URL);
+		assert_eq!(OcwDemo::attestation_source(b"github-org".to_vec()), Some(GITHUB_URL.to_vec()));
+		System::assert_last_event(Event::OcwDemo(crate::Event::AttestationSourceSet(
+			b"github-org".to_vec(),
+			Some(GITHUB_URL.to_vec()),
+		)));
+
+		assert_ok!(OcwDemo::set_attestation_source(Origin::root(), b"github-org".to_vec(), None));
+		assert_eq!(OcwDemo::attestation_source(b"github-org".to_vec()), None);
+	});
+}
+
+#[test]
+fn offchain_worker_attests_documents() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		set_attestation_source(b"github-org", GITHUB_URL);
+		add_reporter(local);
+
+		// the attestation task runs at offset 3
+		expect_get(&state, GITHUB_URL, GITHUB_ORG);
+		run_worker(3);
+		assert_eq!(dispatch_pool(&pool_state), 1);
+
+		assert_eq!(
+			OcwDemo::attestation(b"github-org".to_vec()),
+			Some(Attestation { hash: hash(GITHUB_ORG), block_number: 3 })
+		);
+		System::assert_last_event(Event::OcwDemo(crate::Event::DocumentAttested(
+			b"github-org".to_vec(),
+			hash(GITHUB_ORG),
+			3,
+		)));
+	});
+
+	// the document itself is only in the offchain database
+	t.persist_offchain_overlay();
+	let key = crate::document_key(b"github-org", &3u64);
+	assert_eq!(t.offchain_db().get(sp_offchain::STORAGE_PREFIX, &key), Some(GITHUB_ORG.to_vec()));
+}
+
+#[test]
+fn offchain_worker_does_not_attest_unchanged_or_invalid_documents() {
+	let (mut t, state, pool_state, local) = offchain_ext();
+	t.execute_with(|| {
+		set_attestation_source(b"github-org", GITHUB_URL);
+		add_reporter(local);
+		expect_get(&state, GITHUB_URL, GITHUB_ORG);
+		run_worker(3);
+		assert_eq!(dispatch_pool(&pool_state), 1);
+
+		expect_get(&state, GITHUB_URL, GITHUB_ORG);
+		run_worker(8);
+		assert!(pool_state.read().transactions.is_empty());
+
+		expect_get(&state, GITHUB_URL, b"<html>rate limited</html>");
+		run_worker(13);
+		assert!(pool_state.read().transactions.is_empty());
+		assert_eq!(OcwDemo::attestation(b"github-org".to_vec()).unwrap().block_number, 3);
+	});
+}
+
+#[test]
+fn validate_unsigned_rejects_invalid_documents() {
+	let (mut t, _, _, local) = offchain_ext();
+	t.execute_with(|| {
+		System::set_block_number(10);
+		let call = signed_document(local, GITHUB_ORG, 10);
+
+		// the source has to be registered
+		assert_eq!(validate(&call), InvalidTransaction::Call.into());
+		set_attestation_source(b"github-org", GITHUB_URL);
+
+		// the document signed by a reporter
+		assert_eq!(validate(&call), InvalidTransaction::BadSigner.into());
+		add_reporter(local);
+		assert_ok!(validate(&call));
+		// which is not tampered with
+		let call = match call {
+			crate::Call::submit_document_unsigned_with_signed_payload(mut payload, signature) => {
+				payload.document = br#"{"login":"someone-else"}"#.to_vec();
+				crate::Call::submit_document_unsigned_with_signed_payload(payload, signature)
+			},
+			_ => unreachable!(),
+		};
+		assert_eq!(validate(&call), InvalidTransaction::BadProof.into());
+
+		// within the size limit
+		assert_eq!(
+			validate(&signed_document(local, &[b' '; 1025], 10)),
+			InvalidTransaction::ExhaustsResources.into()
+		);
+
+		// and newer than the attested document
+		assert_ok!(signed_document(local, GITHUB_ORG, 9).dispatch(Origin::none()));
+		assert_eq!(
+			validate(&signed_document(local, GITHUB_ORG, 9)),
+			InvalidTransaction::Stale.into()
+		);
+		assert_noop!(
+			signed_document(local, GITHUB_ORG, 8).dispatch(Origin::none()),
+			Error::<Test>::StaleDocument
+		);
+		assert_ok!(validate(&signed_document(local, GITHUB_ORG, 10)));
+	});
+}
+
+#[test]
+fn migration_should_clear_the_version_0_prices() {
+	new_test_ext().execute_with(|| {
+		// version 0 kept the prices of DOT in a single value at the prefix of the map
+		StorageVersion::new(0).put::<OcwDemo>();
+		let old: VecDeque<(u64, Permill)> = vec![(1, Permill::from_percent(50))].into();
+		unhashed::put(&Prices::<Test>::final_prefix(), &old);
+
+		<OcwDemo as Hooks<u64>>::on_runtime_upgrade();
+		assert_eq!(unhashed::get_raw(&Prices::<Test>::final_prefix()), None);
+		assert_eq!(StorageVersion::get::<OcwDemo>(), 1);
+	});
+}
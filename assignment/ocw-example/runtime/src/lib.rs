@@ -17,7 +17,7 @@ use sp_runtime::{
 	traits::{
 		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature, SaturatedConversion,
 };
 use sp_std::prelude::*;
@@ -276,11 +276,36 @@ impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const OcwMaxSourcesPerAsset: u32 = 5;
+	pub const OcwMaxReporters: u32 = 10;
+	// a single reporter is enough on a development chain
+	pub const OcwQuorum: u32 = 1;
+	pub const OcwMaxDeviation: Permill = Permill::from_percent(5);
+	pub const OcwUnsignedInterval: BlockNumber = 3;
+	pub const OcwPayloadLongevity: BlockNumber = 5;
+	pub const OcwUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+	pub OcwStatsWindows: Vec<BlockNumber> = sp_std::vec![10 * MINUTES, HOURS];
+	// prices are aggregated about every fifth block, enough to cover an hour
+	pub const OcwMaxObservations: u32 = 128;
+	pub const OcwMaxDocumentLen: u32 = 16 * 1024;
+}
+
 /// For pallet-ocw
 impl pallet_ocw::Config for Runtime {
 	type AuthorityId = pallet_ocw::crypto::TestAuthId;
 	type Call = Call;
 	type Event = Event;
+	type MaxSourcesPerAsset = OcwMaxSourcesPerAsset;
+	type MaxReporters = OcwMaxReporters;
+	type Quorum = OcwQuorum;
+	type MaxDeviation = OcwMaxDeviation;
+	type UnsignedInterval = OcwUnsignedInterval;
+	type PayloadLongevity = OcwPayloadLongevity;
+	type UnsignedPriority = OcwUnsignedPriority;
+	type StatsWindows = OcwStatsWindows;
+	type MaxObservations = OcwMaxObservations;
+	type MaxDocumentLen = OcwMaxDocumentLen;
 }
 
 impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
@@ -350,7 +375,7 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
-		OcwDemo: pallet_ocw::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		OcwDemo: pallet_ocw::{Pallet, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -512,6 +537,27 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_ocw_runtime_api::OcwApi<Block, BlockNumber> for Runtime {
+		fn aggregated_price(symbol: Vec<u8>) -> Option<(sp_runtime::FixedU128, u32, BlockNumber)> {
+			OcwDemo::aggregated_price(symbol)
+				.map(|price| (price.price, price.round, price.block_number))
+		}
+
+		fn task_schedules() -> Vec<(pallet_ocw::OffchainTask, pallet_ocw::TaskSchedule<BlockNumber>)> {
+			OcwDemo::task_schedules()
+		}
+
+		fn attestation(source: Vec<u8>) -> Option<pallet_ocw::Attestation<BlockNumber>> {
+			OcwDemo::attestation(source)
+		}
+	}
+
+	impl pallet_ocw_runtime_api::PriceApi<Block, BlockNumber> for Runtime {
+		fn price_stats(symbol: Vec<u8>) -> Vec<(BlockNumber, pallet_ocw::PriceStats<BlockNumber>)> {
+			OcwDemo::price_stats(&symbol)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
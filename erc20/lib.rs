@@ -2,19 +2,62 @@
 
 use ink_lang as ink;
 
+// receiver side of `transfer_and_call`, implemented by contracts accepting deposits
+#[ink::trait_definition]
+pub trait TokenReceiver {
+    // called after `value` tokens were moved from `from` to this contract by `operator`,
+    // return false to reject the deposit and revert the transfer
+    #[ink(message)]
+    fn on_token_received(
+        &mut self,
+        operator: ink_env::AccountId,
+        from: ink_env::AccountId,
+        value: u128,
+        data: ink_prelude::vec::Vec<u8>,
+    ) -> bool;
+}
+
 #[ink::contract]
 mod erc20 {
+    // heap string for token metadata and vec for checkpoints in no_std
+    use ink_prelude::{string::String, vec::Vec};
     // storage type in ink_storage
     use ink_storage::{
-        collections::HashMap,
-        lazy::Lazy,
+        traits::SpreadAllocate,
+        Mapping,
     };
     // ERC20 balance pool
     #[ink(storage)]
+    #[derive(SpreadAllocate)]
     pub struct Erc20 {
-        total_supply: Lazy<Balance>,
-        balances: HashMap<AccountId, Balance>,
-        allowances: HashMap<(AccountId,AccountId), Balance>,
+        total_supply: Balance,
+        balances: Mapping<AccountId, Balance>,
+        allowances: Mapping<(AccountId,AccountId), Balance>,
+        // account allowed to mint and to hand over ownership
+        owner: AccountId,
+        // optional token metadata
+        name: Option<String>,
+        symbol: Option<String>,
+        decimals: u8,
+        // transfers are halted while set
+        paused: bool,
+        // accounts which can neither send nor receive
+        frozen: Mapping<AccountId, ()>,
+        // id of the latest snapshot, 0 before the first one
+        snapshot_id: u32,
+        // per account (snapshot id, balance at that snapshot) checkpoints by position,
+        // ascending by id, kept apart so that a transfer does not load every checkpoint
+        account_snapshots: Mapping<(AccountId, u32), (u32, Balance)>,
+        // per account number of checkpoints
+        account_snapshot_counts: Mapping<AccountId, u32>,
+        // (snapshot id, total supply) checkpoints by position, ascending by id,
+        // a mapping so that messages do not load every checkpoint
+        total_supply_snapshots: Mapping<u32, (u32, Balance)>,
+        // number of total supply checkpoints
+        total_supply_snapshot_count: u32,
+        // per owner count of accepted permits, part of the signed payload,
+        // so an accepted permit no longer matches the digest and cannot be replayed
+        nonces: Mapping<AccountId, u64>,
     }
     // trasnfer event defination
     #[ink(event)]
@@ -33,13 +76,55 @@ mod erc20 {
         spender: AccountId,
         value: Balance,
     }
+    // ownership transfer event defination
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+    // pause event defination
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+    // unpause event defination
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+    // freeze event defination, frozen is false when the account is released
+    #[ink(event)]
+    pub struct Frozen {
+        #[ink(topic)]
+        account: AccountId,
+        frozen: bool,
+    }
+    // snapshot event defination
+    #[ink(event)]
+    pub struct Snapshot {
+        id: u32,
+    }
     // help generate metadata
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
         InsufficientBalance,
         InsufficientApproval,
+        NotOwner,
+        Overflow,
+        Paused,
+        AccountFrozen,
+        InvalidSnapshotId,
+        PermitExpired,
+        InvalidSignature,
+        ReceiverRejected,
     }
+    // tag mixed into every permit payload, so the signature is useless elsewhere
+    const PERMIT_DOMAIN: &[u8] = b"erc20::permit";
     // rename Result
     pub type Result<T> = core::result::Result<T, Error>;
     // implement of ERC20
@@ -47,36 +132,118 @@ mod erc20 {
         // construct smart contract with a start supply
         #[ink(constructor)]
         pub fn new(supply: Balance) -> Self {
-            let caller = Self::env().caller();
-            let mut balances = HashMap::new();
-            balances.insert(caller, supply);
-
-            Self::env().emit_event(Transfer {
-                from: None,
-                to: Some(caller),
-                value: supply,
-            });
+            Self::new_with_metadata(supply, None, None, 0)
+        }
+        // construct smart contract with a start supply and token metadata
+        #[ink(constructor)]
+        pub fn new_with_metadata(
+            supply: Balance,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8
+        ) -> Self {
+            // mapping fields are allocated in place, so build the contract through initialize_contract
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                let caller = Self::env().caller();
+                contract.balances.insert(&caller, &supply);
+                contract.total_supply = supply;
+                contract.owner = caller;
+                contract.name = name;
+                contract.symbol = symbol;
+                contract.decimals = decimals;
 
-            Self {
-                total_supply: Lazy::new(supply),
-                balances,
-                allowances: HashMap::new(),
-            }
+                Self::env().emit_event(Transfer {
+                    from: None,
+                    to: Some(caller),
+                    value: supply,
+                });
+            })
         }
         // get total supply
         #[ink(message)]
         pub fn total_supply(&self) -> Balance {
-            *self.total_supply
+            self.total_supply
         }
         // get a balance of an account
         #[ink(message)]
         pub fn balance_of(&self, who: AccountId) -> Balance {
-            self.balances.get(&who).copied().unwrap_or(0)
+            self.balances.get(&who).unwrap_or_default()
         }
         // get allowance of two accounts
         #[ink(message)]
         pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            self.allowances.get(&(owner, spender)).copied().unwrap_or(0)
+            self.allowances.get(&(owner, spender)).unwrap_or_default()
+        }
+        // get balance of an account at the time a snapshot was taken
+        #[ink(message)]
+        pub fn balance_of_at(&self, who: AccountId, snapshot_id: u32) -> Result<Balance> {
+            self.ensure_snapshot_id(snapshot_id)?;
+            let count = self.account_snapshot_counts.get(&who).unwrap_or_default();
+            Ok(Self::value_at(count, snapshot_id, |index| self.account_snapshots.get(&(who, index)))
+                .unwrap_or_else(|| self.balance_of(who)))
+        }
+        // get total supply at the time a snapshot was taken
+        #[ink(message)]
+        pub fn total_supply_at(&self, snapshot_id: u32) -> Result<Balance> {
+            self.ensure_snapshot_id(snapshot_id)?;
+            let count = self.total_supply_snapshot_count;
+            Ok(Self::value_at(count, snapshot_id, |index| self.total_supply_snapshots.get(&index))
+                .unwrap_or_else(|| self.total_supply()))
+        }
+        // get number of permits accepted for an owner
+        #[ink(message)]
+        pub fn nonces(&self, owner: AccountId) -> u64 {
+            self.nonces.get(&owner).unwrap_or_default()
+        }
+        // get the hash an owner has to sign for a permit with the current nonce
+        #[ink(message)]
+        pub fn permit_digest(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp
+        ) -> [u8; 32] {
+            let payload = (
+                PERMIT_DOMAIN,
+                self.env().account_id(),
+                owner,
+                spender,
+                value,
+                self.nonces(owner),
+                deadline,
+            );
+            self.env().hash_encoded::<ink_env::hash::Blake2x256, _>(&payload)
+        }
+        // get token name
+        #[ink(message)]
+        pub fn name(&self) -> Option<String> {
+            self.name.clone()
+        }
+        // get token symbol
+        #[ink(message)]
+        pub fn symbol(&self) -> Option<String> {
+            self.symbol.clone()
+        }
+        // get number of decimals used to display a balance
+        #[ink(message)]
+        pub fn decimals(&self) -> u8 {
+            self.decimals
+        }
+        // check if transfers are halted
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+        // check if an account is frozen
+        #[ink(message)]
+        pub fn is_frozen(&self, who: AccountId) -> bool {
+            self.frozen.get(&who).is_some()
+        }
+        // get owner of the contract
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
         }
         // transaction steps, start by self to destination address with value
         #[ink(message)]
@@ -84,19 +251,66 @@ mod erc20 {
             let from = self.env().caller();
             self.inner_transfer(from, to, value)
         }
+        // pay many accounts at once, either every transfer happens or none
+        #[ink(message)]
+        pub fn batch_transfer(&mut self, transfers: Vec<(AccountId, Balance)>) -> Result<()> {
+            let from = self.env().caller();
+            // check the whole batch first, storage is not rolled back on error
+            self.ensure_not_paused()?;
+            let mut total: Balance = 0;
+            for (to, value) in &transfers {
+                if self.is_frozen(from) || self.is_frozen(*to) {
+                    return Err(Error::AccountFrozen);
+                }
+                total = total.checked_add(*value).ok_or(Error::Overflow)?;
+            }
+            if self.balance_of(from) < total {
+                return Err(Error::InsufficientBalance);
+            }
+
+            for (to, value) in transfers {
+                self.inner_transfer(from, to, value)?;
+            }
+            Ok(())
+        }
+        // transfer to a contract and notify it through TokenReceiver in the same call,
+        // the transfer is reverted if the receiver rejects it or the call fails
+        #[ink(message)]
+        pub fn transfer_and_call(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<()> {
+            let from = self.env().caller();
+            self.inner_transfer(from, to, value)?;
+            // the caller is both operator and sender here, an account without
+            // contract code fails the call and rejects the deposit too
+            if !matches!(receiver::notify(to, from, from, value, data), Ok(true)) {
+                receiver::revert(Error::ReceiverRejected);
+            }
+            Ok(())
+        }
         // approval steps, start by self to destination address with value
         #[ink(message)]
         pub fn approve(&mut self, to: AccountId, value: Balance) -> Result<()> {
             let owner = self.env().caller();
-            self.allowances.insert((owner, to), value);
-            self.env().emit_event(
-                Approval {
-                    owner,
-                    spender: to,
-                    value
-                }
-            );
-
+            self.inner_approve(owner, to, value);
+            Ok(())
+        }
+        // raise allowance relatively, avoiding the approve race condition
+        #[ink(message)]
+        pub fn increase_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let allowance = self.allowance(owner, spender)
+                .checked_add(delta)
+                .ok_or(Error::Overflow)?;
+            self.inner_approve(owner, spender, allowance);
+            Ok(())
+        }
+        // lower allowance relatively, avoiding the approve race condition
+        #[ink(message)]
+        pub fn decrease_allowance(&mut self, spender: AccountId, delta: Balance) -> Result<()> {
+            let owner = self.env().caller();
+            let allowance = self.allowance(owner, spender)
+                .checked_sub(delta)
+                .ok_or(Error::InsufficientApproval)?;
+            self.inner_approve(owner, spender, allowance);
             Ok(())
         }
         // ask transaction by other address, depending on the allowance
@@ -107,6 +321,7 @@ mod erc20 {
             to: AccountId, 
             value: Balance
         ) -> Result<()> {
+            self.ensure_not_paused()?;
             let caller = self.env().caller();
             let allowance = self.allowance(from, caller);
             if allowance < value {
@@ -114,7 +329,139 @@ mod erc20 {
             }
 
             self.inner_transfer(from, to, value)?;
-            self.allowances.insert((from, caller), allowance - value);
+            self.allowances.insert(&(from, caller), &(allowance - value));
+            Ok(())
+        }
+        // create new tokens for an account, owner only.
+        // like transfers, minting is halted while paused and a frozen account cannot receive
+        #[ink(message)]
+        pub fn mint(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            self.ensure_not_paused()?;
+            if self.is_frozen(to) {
+                return Err(Error::AccountFrozen);
+            }
+            let total_supply = self.total_supply().checked_add(value).ok_or(Error::Overflow)?;
+            let to_balance = self.balance_of(to).checked_add(value).ok_or(Error::Overflow)?;
+            self.update_account_snapshot(to);
+            self.update_total_supply_snapshot();
+            self.balances.insert(&to, &to_balance);
+            self.total_supply = total_supply;
+            self.env().emit_event(
+                Transfer {
+                    from: None,
+                    to: Some(to),
+                    value,
+                }
+            );
+
+            Ok(())
+        }
+        // destroy tokens of the caller
+        #[ink(message)]
+        pub fn burn(&mut self, value: Balance) -> Result<()> {
+            let from = self.env().caller();
+            self.inner_burn(from, value)
+        }
+        // destroy tokens of other address, depending on the allowance
+        #[ink(message)]
+        pub fn burn_from(&mut self, from: AccountId, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let allowance = self.allowance(from, caller);
+            if allowance < value {
+                return Err(Error::InsufficientApproval);
+            }
+
+            self.inner_burn(from, value)?;
+            self.allowances.insert(&(from, caller), &(allowance - value));
+            Ok(())
+        }
+        // hand the owner role to another account, owner only
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let previous_owner = self.owner;
+            self.owner = new_owner;
+            self.env().emit_event(
+                OwnershipTransferred {
+                    previous_owner,
+                    new_owner,
+                }
+            );
+
+            Ok(())
+        }
+        // record current balances under a new snapshot id, owner only
+        #[ink(message)]
+        pub fn snapshot(&mut self) -> Result<u32> {
+            self.ensure_owner()?;
+            let id = self.snapshot_id.checked_add(1).ok_or(Error::Overflow)?;
+            self.snapshot_id = id;
+            self.env().emit_event(Snapshot { id });
+            Ok(id)
+        }
+        // approve by an owner's ecdsa signature, so the spender pays for the call.
+        // the owner account has to be derived from the ecdsa key, that is
+        // blake2_256 of the compressed public key
+        #[ink(message)]
+        pub fn permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: Timestamp,
+            signature: [u8; 65]
+        ) -> Result<()> {
+            if self.env().block_timestamp() > deadline {
+                return Err(Error::PermitExpired);
+            }
+            let digest = self.permit_digest(owner, spender, value, deadline);
+            let mut public_key = [0u8; 33];
+            self.env()
+                .ecdsa_recover(&signature, &digest, &mut public_key)
+                .map_err(|_| Error::InvalidSignature)?;
+            let signer = AccountId::from(self.env().hash_bytes::<ink_env::hash::Blake2x256>(&public_key));
+            if signer != owner {
+                return Err(Error::InvalidSignature);
+            }
+
+            let nonce = self.nonces(owner).checked_add(1).ok_or(Error::Overflow)?;
+            self.nonces.insert(&owner, &nonce);
+            self.inner_approve(owner, spender, value);
+            Ok(())
+        }
+        // halt all transfers, owner only
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.paused = true;
+            let by = self.env().caller();
+            self.env().emit_event(Paused { by });
+            Ok(())
+        }
+        // resume transfers, owner only
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.paused = false;
+            let by = self.env().caller();
+            self.env().emit_event(Unpaused { by });
+            Ok(())
+        }
+        // block an account from sending and receiving, owner only
+        #[ink(message)]
+        pub fn freeze(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.frozen.insert(&account, &());
+            self.env().emit_event(Frozen { account, frozen: true });
+            Ok(())
+        }
+        // release a frozen account, owner only
+        #[ink(message)]
+        pub fn unfreeze(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.frozen.remove(&account);
+            self.env().emit_event(Frozen { account, frozen: false });
             Ok(())
         }
         
@@ -125,14 +472,23 @@ mod erc20 {
             to: AccountId, 
             value: Balance
         ) -> Result<()> {
+            self.ensure_not_paused()?;
+            if self.is_frozen(from) || self.is_frozen(to) {
+                return Err(Error::AccountFrozen);
+            }
             let from_balance = self.balance_of(from);
             if from_balance < value {
                 return Err(Error::InsufficientBalance);
             }
 
-            self.balances.insert(from, from_balance - value);
-            let to_balance = self.balance_of(to);
-            self.balances.insert(to, to_balance + value);
+            // check every sum before writing, so a failure leaves storage untouched
+            if from != to {
+                let to_balance = self.balance_of(to).checked_add(value).ok_or(Error::Overflow)?;
+                self.update_account_snapshot(from);
+                self.update_account_snapshot(to);
+                self.balances.insert(&from, &(from_balance - value));
+                self.balances.insert(&to, &to_balance);
+            }
             self.env().emit_event(
                 Transfer {
                     from: Some(from),
@@ -143,5 +499,1015 @@ mod erc20 {
 
             Ok(())
         }
+
+        // inner steps of approval
+        fn inner_approve(&mut self, owner: AccountId, spender: AccountId, value: Balance) {
+            self.allowances.insert(&(owner, spender), &value);
+            self.env().emit_event(
+                Approval {
+                    owner,
+                    spender,
+                    value
+                }
+            );
+        }
+
+        // inner steps of burning, halted while paused and for a frozen account
+        fn inner_burn(&mut self, from: AccountId, value: Balance) -> Result<()> {
+            self.ensure_not_paused()?;
+            if self.is_frozen(from) {
+                return Err(Error::AccountFrozen);
+            }
+            let from_balance = self.balance_of(from);
+            if from_balance < value {
+                return Err(Error::InsufficientBalance);
+            }
+
+            self.update_account_snapshot(from);
+            self.update_total_supply_snapshot();
+            self.balances.insert(&from, &(from_balance - value));
+            self.total_supply -= value;
+            self.env().emit_event(
+                Transfer {
+                    from: Some(from),
+                    to: None,
+                    value,
+                }
+            );
+
+            Ok(())
+        }
+
+        // check if caller is the owner
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        // keep the balance an account had when the current snapshot was taken,
+        // must be called before the balance changes
+        fn update_account_snapshot(&mut self, who: AccountId) {
+            let current = self.balance_of(who);
+            let snapshot_id = self.snapshot_id;
+            if snapshot_id == 0 {
+                return;
+            }
+            let count = self.account_snapshot_counts.get(&who).unwrap_or_default();
+            let last_id = count
+                .checked_sub(1)
+                .and_then(|last| self.account_snapshots.get(&(who, last)))
+                .map(|(id, _)| id);
+            if last_id.map_or(true, |id| id < snapshot_id) {
+                self.account_snapshots.insert(&(who, count), &(snapshot_id, current));
+                self.account_snapshot_counts.insert(&who, &(count + 1));
+            }
+        }
+
+        // same as update_account_snapshot, for the total supply
+        fn update_total_supply_snapshot(&mut self) {
+            let current = self.total_supply();
+            let snapshot_id = self.snapshot_id;
+            let count = self.total_supply_snapshot_count;
+            if snapshot_id == 0 {
+                return;
+            }
+            let last_id = count
+                .checked_sub(1)
+                .and_then(|last| self.total_supply_snapshots.get(&last))
+                .map(|(id, _)| id);
+            if last_id.map_or(true, |id| id < snapshot_id) {
+                self.total_supply_snapshots.insert(&count, &(snapshot_id, current));
+                self.total_supply_snapshot_count = count + 1;
+            }
+        }
+
+        // value at a snapshot is the first checkpoint written at or after it,
+        // None means the value did not change since and the current one applies.
+        // binary search over the `count` checkpoints by position, read with `get`
+        fn value_at(count: u32, snapshot_id: u32, get: impl Fn(u32) -> Option<(u32, Balance)>) -> Option<Balance> {
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                match get(mid) {
+                    Some((id, _)) if id < snapshot_id => low = mid + 1,
+                    _ => high = mid,
+                }
+            }
+            if low == count {
+                return None;
+            }
+            get(low).map(|(_, value)| value)
+        }
+
+        // check if a snapshot id has been taken
+        fn ensure_snapshot_id(&self, snapshot_id: u32) -> Result<()> {
+            if snapshot_id == 0 || snapshot_id > self.snapshot_id {
+                return Err(Error::InvalidSnapshotId);
+            }
+            Ok(())
+        }
+
+        // check if transfers are allowed
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            Ok(())
+        }
+    }
+
+    // the cross-contract steps of transfer_and_call. The off-chain env can neither call
+    // contracts nor implement seal_return, so the unit tests swap this module for
+    // tests::mock_receiver, and the real call and the rollback on reject are only checked by
+    // hand on a node: deploy a TokenReceiver contract, then transfer_and_call to it while it
+    // accepts, while it rejects (balances unchanged), and to an account without code
+    #[cfg(not(test))]
+    mod receiver {
+        use super::*;
+
+        // call TokenReceiver::on_token_received of `to`
+        pub fn notify(
+            to: AccountId,
+            operator: AccountId,
+            from: AccountId,
+            value: Balance,
+            data: Vec<u8>
+        ) -> ink_env::Result<bool> {
+            use ink_env::call::{build_call, Call, ExecutionInput, Selector};
+
+            build_call::<Environment>()
+                .call_type(Call::new().callee(to).gas_limit(0))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "TokenReceiver::on_token_received"
+                    )))
+                    .push_arg(operator)
+                    .push_arg(from)
+                    .push_arg(value)
+                    .push_arg(data),
+                )
+                .returns::<bool>()
+                .fire()
+        }
+
+        // end the message with `error` and roll back its storage changes,
+        // returning Err alone would keep them
+        pub fn revert(error: Error) -> ! {
+            ink_env::return_value::<Result<()>>(
+                ink_env::ReturnFlags::default().set_reverted(true),
+                &Err(error),
+            )
+        }
+    }
+
+    #[cfg(test)]
+    use tests::mock_receiver as receiver;
+
+    // off-chain unit tests
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink_lang as ink;
+
+        type Event = <Erc20 as ::ink_lang::reflect::ContractEventBase>::Type;
+
+        thread_local! {
+            // accounts with a TokenReceiver contract, and whether it accepts deposits
+            static RECEIVERS: core::cell::RefCell<Vec<(AccountId, bool)>> = Default::default();
+            // (to, operator, from, value, data) of every TokenReceiver call
+            static RECEIVER_CALLS: core::cell::RefCell<Vec<(AccountId, AccountId, AccountId, Balance, Vec<u8>)>> =
+                Default::default();
+        }
+
+        // deploy a mocked TokenReceiver contract at `account`
+        fn set_receiver(account: AccountId, accepts: bool) {
+            RECEIVERS.with(|receivers| receivers.borrow_mut().push((account, accepts)));
+        }
+
+        fn receiver_calls() -> Vec<(AccountId, AccountId, AccountId, Balance, Vec<u8>)> {
+            RECEIVER_CALLS.with(|calls| calls.borrow().clone())
+        }
+
+        // stands for the receiver module, which the off-chain env cannot run
+        pub(super) mod mock_receiver {
+            use super::*;
+
+            // calling an account without a receiver fails like calling an account
+            // without contract code
+            pub fn notify(
+                to: AccountId,
+                operator: AccountId,
+                from: AccountId,
+                value: Balance,
+                data: Vec<u8>,
+            ) -> ink_env::Result<bool> {
+                let accepts = RECEIVERS.with(|receivers| {
+                    receivers.borrow().iter().find(|(account, _)| *account == to).map(|(_, accepts)| *accepts)
+                }).ok_or(ink_env::Error::NotCallable)?;
+                RECEIVER_CALLS.with(|calls| calls.borrow_mut().push((to, operator, from, value, data)));
+                Ok(accepts)
+            }
+
+            // a revert is seen as a panic, the rollback itself is not simulated
+            pub fn revert(error: Error) -> ! {
+                panic!("reverted with {:?}", error)
+            }
+        }
+
+        fn default_accounts() -> ink_env::test::DefaultAccounts<ink_env::DefaultEnvironment> {
+            ink_env::test::default_accounts::<ink_env::DefaultEnvironment>()
+        }
+
+        // make the next message called by `caller`
+        fn set_next_caller(caller: AccountId) {
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(caller);
+        }
+
+        fn recorded_events() -> Vec<ink_env::test::EmittedEvent> {
+            ink_env::test::recorded_events().collect()
+        }
+
+        fn decode_event(event: &ink_env::test::EmittedEvent) -> Event {
+            <Event as scale::Decode>::decode(&mut &event.data[..]).expect("invalid event data")
+        }
+
+        fn last_event() -> Event {
+            decode_event(recorded_events().last().expect("no event emitted"))
+        }
+
+        fn assert_transfer(event: Event, from: Option<AccountId>, to: Option<AccountId>, value: Balance) {
+            if let Event::Transfer(Transfer { from: f, to: t, value: v }) = event {
+                assert_eq!((f, t, v), (from, to, value));
+            } else {
+                panic!("expected a Transfer event");
+            }
+        }
+
+        // topic of a value, same as the one the contract computes on emit:
+        // short encodings are zero padded, longer ones are blake2x256 hashed
+        fn encoded_into_hash<T: scale::Encode>(entity: &T) -> Hash {
+            use ink_env::{
+                hash::{Blake2x256, CryptoHash, HashOutput},
+                Clear,
+            };
+            let mut result = Hash::clear();
+            let len_result = result.as_ref().len();
+            let encoded = entity.encode();
+            let len_encoded = encoded.len();
+            if len_encoded <= len_result {
+                result.as_mut()[..len_encoded].copy_from_slice(&encoded);
+                return result;
+            }
+            let mut hash_output = <<Blake2x256 as HashOutput>::Type as Default>::default();
+            <Blake2x256 as CryptoHash>::hash(&encoded, &mut hash_output);
+            let copy_len = core::cmp::min(hash_output.len(), len_result);
+            result.as_mut()[..copy_len].copy_from_slice(&hash_output[..copy_len]);
+            result
+        }
+
+        fn assert_topics(event: &ink_env::test::EmittedEvent, expected: Vec<Hash>) {
+            assert_eq!(event.topics.len(), expected.len(), "wrong number of topics");
+            for (n, (actual, expected)) in event.topics.iter().zip(expected).enumerate() {
+                let actual = <Hash as scale::Decode>::decode(&mut &actual[..]).expect("invalid topic");
+                assert_eq!(actual, expected, "topic {} does not match", n);
+            }
+        }
+
+        // check data and topics of an emitted Transfer event
+        fn assert_transfer_event(
+            event: &ink_env::test::EmittedEvent,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            value: Balance,
+        ) {
+            use ink_env::topics::PrefixedValue;
+            assert_transfer(decode_event(event), from, to, value);
+            assert_topics(event, vec![
+                encoded_into_hash(&PrefixedValue { prefix: b"", value: b"Erc20::Transfer" }),
+                encoded_into_hash(&PrefixedValue { prefix: b"Erc20::Transfer::from", value: &from }),
+            ]);
+        }
+
+        // check data and topics of an emitted Approval event
+        fn assert_approval_event(
+            event: &ink_env::test::EmittedEvent,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+        ) {
+            use ink_env::topics::PrefixedValue;
+            if let Event::Approval(Approval { owner: o, spender: s, value: v }) = decode_event(event) {
+                assert_eq!((o, s, v), (owner, spender, value));
+            } else {
+                panic!("expected an Approval event");
+            }
+            assert_topics(event, vec![
+                encoded_into_hash(&PrefixedValue { prefix: b"", value: b"Erc20::Approval" }),
+                encoded_into_hash(&PrefixedValue { prefix: b"Erc20::Approval::owner", value: &owner }),
+                encoded_into_hash(&PrefixedValue { prefix: b"Erc20::Approval::spender", value: &spender }),
+            ]);
+        }
+
+        #[ink::test]
+        fn transfer_flow_emits_events() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.approve(accounts.bob, 20), Ok(()));
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.transfer_from(accounts.alice, accounts.eve, 15), Ok(()));
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 4);
+            assert_transfer_event(&events[0], None, Some(accounts.alice), 100);
+            assert_transfer_event(&events[1], Some(accounts.alice), Some(accounts.bob), 10);
+            assert_approval_event(&events[2], accounts.alice, accounts.bob, 20);
+            assert_transfer_event(&events[3], Some(accounts.alice), Some(accounts.eve), 15);
+        }
+
+        #[ink::test]
+        fn failed_messages_emit_no_events() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 101), Err(Error::InsufficientBalance));
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.bob, 1),
+                Err(Error::InsufficientApproval)
+            );
+            // only the constructor transfer is recorded
+            let events = recorded_events();
+            assert_eq!(events.len(), 1);
+            assert_transfer_event(&events[0], None, Some(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn allowance_changes_emit_approval_events() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.increase_allowance(accounts.bob, 7), Ok(()));
+            assert_eq!(erc20.decrease_allowance(accounts.bob, 2), Ok(()));
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 3);
+            assert_approval_event(&events[1], accounts.alice, accounts.bob, 7);
+            assert_approval_event(&events[2], accounts.alice, accounts.bob, 5);
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let accounts = default_accounts();
+            let erc20 = Erc20::new(100);
+            assert_eq!(erc20.total_supply(), 100);
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+            assert_eq!(erc20.name(), None);
+            assert_eq!(erc20.symbol(), None);
+            assert_eq!(erc20.decimals(), 0);
+            assert_transfer(last_event(), None, Some(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn new_with_metadata_works() {
+            let erc20 = Erc20::new_with_metadata(
+                100,
+                Some(String::from("Kitty Token")),
+                Some(String::from("KTY")),
+                12,
+            );
+            assert_eq!(erc20.name(), Some(String::from("Kitty Token")));
+            assert_eq!(erc20.symbol(), Some(String::from("KTY")));
+            assert_eq!(erc20.decimals(), 12);
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 90);
+            assert_eq!(erc20.balance_of(accounts.bob), 10);
+            assert_transfer(last_event(), Some(accounts.alice), Some(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn transfer_to_self_keeps_balance() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.alice, 10), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+        }
+
+        #[ink::test]
+        fn transfer_fails_when_balance_is_insufficient() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 101), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn approve_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 20), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 20);
+            // approve overwrites the previous allowance
+            assert_eq!(erc20.approve(accounts.bob, 5), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 5);
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 20), Ok(()));
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.transfer_from(accounts.alice, accounts.eve, 15), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 85);
+            assert_eq!(erc20.balance_of(accounts.eve), 15);
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 5);
+            assert_transfer(last_event(), Some(accounts.alice), Some(accounts.eve), 15);
+        }
+
+        #[ink::test]
+        fn transfer_from_fails_when_allowance_is_insufficient() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 10), Ok(()));
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.eve, 15),
+                Err(Error::InsufficientApproval)
+            );
+            assert_eq!(erc20.balance_of(accounts.eve), 0);
+        }
+
+        #[ink::test]
+        fn transfer_from_fails_when_balance_is_insufficient() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 200), Ok(()));
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.eve, 150),
+                Err(Error::InsufficientBalance)
+            );
+            // allowance is not spent by a failed transfer
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 200);
+        }
+
+        #[ink::test]
+        fn increase_allowance_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.increase_allowance(accounts.bob, 5), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 15);
+            if let Event::Approval(e) = last_event() {
+                assert_eq!((e.owner, e.spender, e.value), (accounts.alice, accounts.bob, 15));
+            } else {
+                panic!("expected an Approval event");
+            }
+        }
+
+        #[ink::test]
+        fn increase_allowance_fails_on_overflow() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, Balance::MAX), Ok(()));
+            assert_eq!(erc20.increase_allowance(accounts.bob, 1), Err(Error::Overflow));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), Balance::MAX);
+        }
+
+        #[ink::test]
+        fn decrease_allowance_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.decrease_allowance(accounts.bob, 4), Ok(()));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 6);
+            assert_eq!(erc20.decrease_allowance(accounts.bob, 7), Err(Error::InsufficientApproval));
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 6);
+        }
+
+        #[ink::test]
+        fn mint_fails_on_overflow() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(Balance::MAX);
+            assert_eq!(erc20.mint(accounts.bob, 1), Err(Error::Overflow));
+            assert_eq!(erc20.total_supply(), Balance::MAX);
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.pause(), Ok(()));
+            assert!(erc20.is_paused());
+            assert!(matches!(last_event(), Event::Paused(_)));
+            assert_eq!(erc20.transfer(accounts.bob, 10), Err(Error::Paused));
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                erc20.transfer_from(accounts.alice, accounts.bob, 10),
+                Err(Error::Paused)
+            );
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn unpause_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.pause(), Ok(()));
+            assert_eq!(erc20.unpause(), Ok(()));
+            assert!(!erc20.is_paused());
+            assert!(matches!(last_event(), Event::Unpaused(_)));
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn pause_fails_when_caller_is_not_owner() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.pause(), Err(Error::NotOwner));
+            assert_eq!(erc20.unpause(), Err(Error::NotOwner));
+            assert!(!erc20.is_paused());
+        }
+
+        #[ink::test]
+        fn pause_halts_mint_and_burn() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.pause(), Ok(()));
+            assert_eq!(erc20.mint(accounts.bob, 10), Err(Error::Paused));
+            assert_eq!(erc20.burn(10), Err(Error::Paused));
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.burn_from(accounts.alice, 10), Err(Error::Paused));
+            assert_eq!(erc20.total_supply(), 100);
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn freeze_blocks_mint_and_burn() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.freeze(accounts.bob), Ok(()));
+            assert_eq!(erc20.mint(accounts.bob, 10), Err(Error::AccountFrozen));
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.burn(10), Err(Error::AccountFrozen));
+            assert_eq!(erc20.balance_of(accounts.bob), 50);
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn freeze_blocks_sender_and_receiver() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.freeze(accounts.bob), Ok(()));
+            assert!(erc20.is_frozen(accounts.bob));
+            if let Event::Frozen(e) = last_event() {
+                assert_eq!((e.account, e.frozen), (accounts.bob, true));
+            } else {
+                panic!("expected a Frozen event");
+            }
+            // frozen receiver
+            assert_eq!(erc20.transfer(accounts.bob, 10), Err(Error::AccountFrozen));
+            // frozen sender
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.transfer(accounts.eve, 10), Err(Error::AccountFrozen));
+            assert_eq!(erc20.balance_of(accounts.bob), 50);
+        }
+
+        #[ink::test]
+        fn unfreeze_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.freeze(accounts.bob), Ok(()));
+            assert_eq!(erc20.unfreeze(accounts.bob), Ok(()));
+            assert!(!erc20.is_frozen(accounts.bob));
+            if let Event::Frozen(e) = last_event() {
+                assert_eq!((e.account, e.frozen), (accounts.bob, false));
+            } else {
+                panic!("expected a Frozen event");
+            }
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn freeze_fails_when_caller_is_not_owner() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.freeze(accounts.alice), Err(Error::NotOwner));
+            assert!(!erc20.is_frozen(accounts.alice));
+        }
+
+        #[ink::test]
+        fn snapshot_works() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.snapshot(), Ok(1));
+            assert_eq!(erc20.snapshot(), Ok(2));
+            if let Event::Snapshot(e) = last_event() {
+                assert_eq!(e.id, 2);
+            } else {
+                panic!("expected a Snapshot event");
+            }
+        }
+
+        #[ink::test]
+        fn snapshot_fails_when_caller_is_not_owner() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.snapshot(), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn balance_of_at_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.snapshot(), Ok(1));
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.transfer(accounts.bob, 10), Ok(()));
+            assert_eq!(erc20.snapshot(), Ok(2));
+            // no change since snapshot 2
+            assert_eq!(erc20.snapshot(), Ok(3));
+            assert_eq!(erc20.transfer(accounts.bob, 30), Ok(()));
+
+            assert_eq!(erc20.balance_of_at(accounts.alice, 1), Ok(100));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 1), Ok(0));
+            assert_eq!(erc20.balance_of_at(accounts.alice, 2), Ok(80));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 2), Ok(20));
+            assert_eq!(erc20.balance_of_at(accounts.alice, 3), Ok(80));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 3), Ok(20));
+            assert_eq!(erc20.balance_of(accounts.bob), 50);
+            // untouched account falls back to the current balance
+            assert_eq!(erc20.balance_of_at(accounts.eve, 2), Ok(0));
+        }
+
+        #[ink::test]
+        fn total_supply_at_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.snapshot(), Ok(1));
+            assert_eq!(erc20.mint(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.snapshot(), Ok(2));
+            assert_eq!(erc20.burn(30), Ok(()));
+
+            assert_eq!(erc20.total_supply_at(1), Ok(100));
+            assert_eq!(erc20.total_supply_at(2), Ok(150));
+            assert_eq!(erc20.total_supply(), 120);
+            assert_eq!(erc20.balance_of_at(accounts.bob, 1), Ok(0));
+            assert_eq!(erc20.balance_of_at(accounts.alice, 2), Ok(100));
+            assert_eq!(erc20.balance_of(accounts.alice), 70);
+        }
+
+        #[ink::test]
+        fn snapshot_lookups_search_checkpoints() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            // supply changes after snapshots 1, 2, 4 and 5, none after 3
+            for (id, minted) in [(1, 10), (2, 20), (3, 0), (4, 40), (5, 50)] {
+                assert_eq!(erc20.snapshot(), Ok(id));
+                if minted > 0 {
+                    assert_eq!(erc20.mint(accounts.bob, minted), Ok(()));
+                }
+            }
+            assert_eq!(erc20.snapshot(), Ok(6));
+
+            assert_eq!(erc20.total_supply_at(1), Ok(100));
+            assert_eq!(erc20.total_supply_at(2), Ok(110));
+            assert_eq!(erc20.total_supply_at(3), Ok(130));
+            assert_eq!(erc20.total_supply_at(4), Ok(130));
+            assert_eq!(erc20.total_supply_at(5), Ok(170));
+            assert_eq!(erc20.total_supply_at(6), Ok(220));
+
+            // one checkpoint per snapshot the balance of bob changed after
+            assert_eq!(erc20.account_snapshot_counts.get(&accounts.bob), Some(4));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 1), Ok(0));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 2), Ok(10));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 3), Ok(30));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 4), Ok(30));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 5), Ok(70));
+            assert_eq!(erc20.balance_of_at(accounts.bob, 6), Ok(120));
+        }
+
+        #[ink::test]
+        fn balance_of_at_fails_for_unknown_snapshot() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.balance_of_at(accounts.alice, 0), Err(Error::InvalidSnapshotId));
+            assert_eq!(erc20.total_supply_at(1), Err(Error::InvalidSnapshotId));
+            assert_eq!(erc20.snapshot(), Ok(1));
+            assert_eq!(erc20.balance_of_at(accounts.alice, 2), Err(Error::InvalidSnapshotId));
+        }
+
+        // ecdsa key pair whose derived account receives permits in tests
+        fn permit_signer(seed: u8) -> (secp256k1::SecretKey, AccountId) {
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_slice(&[seed; 32]).expect("valid secret key");
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key).serialize();
+            let mut account = [0u8; 32];
+            ink_env::hash_bytes::<ink_env::hash::Blake2x256>(&public_key, &mut account);
+            (secret_key, AccountId::from(account))
+        }
+
+        fn sign_permit(secret_key: &secp256k1::SecretKey, digest: [u8; 32]) -> [u8; 65] {
+            let secp = secp256k1::Secp256k1::new();
+            let message = secp256k1::Message::from_slice(&digest).expect("32 bytes digest");
+            let (recovery_id, compact) = secp
+                .sign_ecdsa_recoverable(&message, secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
+
+        #[ink::test]
+        fn permit_works() {
+            let accounts = default_accounts();
+            let (secret_key, owner) = permit_signer(1);
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer(owner, 50), Ok(()));
+            let signature = sign_permit(&secret_key, erc20.permit_digest(owner, accounts.bob, 30, 1000));
+
+            // the spender submits the permit
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.permit(owner, accounts.bob, 30, 1000, signature), Ok(()));
+            assert_eq!(erc20.allowance(owner, accounts.bob), 30);
+            assert_eq!(erc20.nonces(owner), 1);
+            assert_approval_event(recorded_events().last().unwrap(), owner, accounts.bob, 30);
+            assert_eq!(erc20.transfer_from(owner, accounts.bob, 30), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.bob), 30);
+        }
+
+        #[ink::test]
+        fn permit_fails_when_replayed() {
+            let accounts = default_accounts();
+            let (secret_key, owner) = permit_signer(1);
+            let mut erc20 = Erc20::new(100);
+            let digest = erc20.permit_digest(owner, accounts.bob, 30, 1000);
+            let signature = sign_permit(&secret_key, digest);
+            assert_eq!(erc20.permit(owner, accounts.bob, 30, 1000, signature), Ok(()));
+            assert_eq!(erc20.nonces(owner), 1);
+            // the nonce moved on, so the same terms hash to another digest
+            assert_ne!(erc20.permit_digest(owner, accounts.bob, 30, 1000), digest);
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                erc20.permit(owner, accounts.bob, 30, 1000, signature),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(erc20.nonces(owner), 1);
+
+            // a permit signed for the new nonce is accepted
+            let signature = sign_permit(&secret_key, erc20.permit_digest(owner, accounts.bob, 30, 1000));
+            assert_eq!(erc20.permit(owner, accounts.bob, 30, 1000, signature), Ok(()));
+            assert_eq!(erc20.nonces(owner), 2);
+        }
+
+        #[ink::test]
+        fn permit_fails_when_expired() {
+            let accounts = default_accounts();
+            let (secret_key, owner) = permit_signer(1);
+            let mut erc20 = Erc20::new(100);
+            let signature = sign_permit(&secret_key, erc20.permit_digest(owner, accounts.bob, 30, 1000));
+            ink_env::test::set_block_timestamp::<ink_env::DefaultEnvironment>(1001);
+            assert_eq!(
+                erc20.permit(owner, accounts.bob, 30, 1000, signature),
+                Err(Error::PermitExpired)
+            );
+            assert_eq!(erc20.allowance(owner, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn permit_fails_when_signer_is_not_owner() {
+            let accounts = default_accounts();
+            let (_, owner) = permit_signer(1);
+            let (other_key, _) = permit_signer(2);
+            let mut erc20 = Erc20::new(100);
+            let signature = sign_permit(&other_key, erc20.permit_digest(owner, accounts.bob, 30, 1000));
+            assert_eq!(
+                erc20.permit(owner, accounts.bob, 30, 1000, signature),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(erc20.nonces(owner), 0);
+        }
+
+        #[ink::test]
+        fn permit_fails_when_terms_are_changed() {
+            let accounts = default_accounts();
+            let (secret_key, owner) = permit_signer(1);
+            let mut erc20 = Erc20::new(100);
+            let signature = sign_permit(&secret_key, erc20.permit_digest(owner, accounts.bob, 30, 1000));
+            // signed for 30, submitted for 300
+            assert_eq!(
+                erc20.permit(owner, accounts.bob, 300, 1000, signature),
+                Err(Error::InvalidSignature)
+            );
+        }
+
+        #[ink::test]
+        fn batch_transfer_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(
+                erc20.batch_transfer(vec![(accounts.bob, 10), (accounts.eve, 20), (accounts.bob, 5)]),
+                Ok(())
+            );
+            assert_eq!(erc20.balance_of(accounts.alice), 65);
+            assert_eq!(erc20.balance_of(accounts.bob), 15);
+            assert_eq!(erc20.balance_of(accounts.eve), 20);
+            let events = recorded_events();
+            assert_eq!(events.len(), 4);
+            assert_transfer_event(&events[2], Some(accounts.alice), Some(accounts.eve), 20);
+        }
+
+        #[ink::test]
+        fn batch_transfer_is_all_or_nothing() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            // the first transfers fit, the total does not
+            assert_eq!(
+                erc20.batch_transfer(vec![(accounts.bob, 60), (accounts.eve, 30), (accounts.frank, 20)]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(erc20.balance_of(accounts.alice), 100);
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+            assert_eq!(erc20.balance_of(accounts.eve), 0);
+            assert_eq!(recorded_events().len(), 1);
+        }
+
+        #[ink::test]
+        fn batch_transfer_fails_when_a_receiver_is_frozen() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.freeze(accounts.eve), Ok(()));
+            assert_eq!(
+                erc20.batch_transfer(vec![(accounts.bob, 10), (accounts.eve, 10)]),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(erc20.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn batch_transfer_fails_on_overflow() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(
+                erc20.batch_transfer(vec![(accounts.bob, Balance::MAX), (accounts.eve, 1)]),
+                Err(Error::Overflow)
+            );
+        }
+
+        #[ink::test]
+        fn transfer_and_call_fails_before_calling_receiver() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_receiver(accounts.django, true);
+            assert_eq!(
+                erc20.transfer_and_call(accounts.django, 101, vec![]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(erc20.pause(), Ok(()));
+            assert_eq!(erc20.transfer_and_call(accounts.django, 1, vec![]), Err(Error::Paused));
+            assert_eq!(erc20.balance_of(accounts.django), 0);
+            assert!(receiver_calls().is_empty());
+        }
+
+        #[ink::test]
+        fn transfer_and_call_works_when_receiver_accepts() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_receiver(accounts.django, true);
+            assert_eq!(erc20.transfer_and_call(accounts.django, 10, vec![1, 2]), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 90);
+            assert_eq!(erc20.balance_of(accounts.django), 10);
+            assert_transfer(last_event(), Some(accounts.alice), Some(accounts.django), 10);
+            assert_eq!(
+                receiver_calls(),
+                vec![(accounts.django, accounts.alice, accounts.alice, 10, vec![1, 2])]
+            );
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "reverted with ReceiverRejected")]
+        fn transfer_and_call_reverts_when_receiver_rejects() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_receiver(accounts.django, false);
+            let _ = erc20.transfer_and_call(accounts.django, 10, vec![]);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "reverted with ReceiverRejected")]
+        fn transfer_and_call_reverts_when_target_is_not_a_contract() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            let _ = erc20.transfer_and_call(accounts.django, 10, vec![]);
+        }
+
+        #[ink::test]
+        fn new_sets_owner() {
+            let accounts = default_accounts();
+            let erc20 = Erc20::new(100);
+            assert_eq!(erc20.owner(), accounts.alice);
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn mint_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.mint(accounts.bob, 50), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.bob), 50);
+            assert_eq!(erc20.total_supply(), 150);
+            assert_transfer(last_event(), None, Some(accounts.bob), 50);
+        }
+
+        #[ink::test]
+        fn mint_fails_when_caller_is_not_owner() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.mint(accounts.bob, 50), Err(Error::NotOwner));
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn burn_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.burn(30), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 70);
+            assert_eq!(erc20.total_supply(), 70);
+            assert_transfer(last_event(), Some(accounts.alice), None, 30);
+        }
+
+        #[ink::test]
+        fn burn_fails_when_balance_is_insufficient() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.burn(101), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn burn_from_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 40), Ok(()));
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.burn_from(accounts.alice, 30), Ok(()));
+            assert_eq!(erc20.balance_of(accounts.alice), 70);
+            assert_eq!(erc20.allowance(accounts.alice, accounts.bob), 10);
+            assert_eq!(erc20.total_supply(), 70);
+            assert_transfer(last_event(), Some(accounts.alice), None, 30);
+        }
+
+        #[ink::test]
+        fn burn_from_fails_when_allowance_is_insufficient() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.approve(accounts.bob, 20), Ok(()));
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.burn_from(accounts.alice, 30), Err(Error::InsufficientApproval));
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        #[ink::test]
+        fn transfer_ownership_works() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(erc20.owner(), accounts.bob);
+            if let Event::OwnershipTransferred(e) = last_event() {
+                assert_eq!((e.previous_owner, e.new_owner), (accounts.alice, accounts.bob));
+            } else {
+                panic!("expected an OwnershipTransferred event");
+            }
+            // previous owner lost the role
+            assert_eq!(erc20.mint(accounts.alice, 1), Err(Error::NotOwner));
+        }
+
+        #[ink::test]
+        fn transfer_ownership_fails_when_caller_is_not_owner() {
+            let accounts = default_accounts();
+            let mut erc20 = Erc20::new(100);
+            set_next_caller(accounts.bob);
+            assert_eq!(erc20.transfer_ownership(accounts.bob), Err(Error::NotOwner));
+            assert_eq!(erc20.owner(), accounts.alice);
+        }
     }
-}  
+}
@@ -0,0 +1,154 @@
+//! Parse decimal prices, as answered by price APIs, into fixed-point numbers.
+//!
+//! The result is the inner value of a `FixedU128`, i.e. the price multiplied by [`ACCURACY`], so
+//! that pallets built against different substrate versions can share the parser and wrap the
+//! value with `FixedU128::from_inner`. Digits beyond the 18th decimal are truncated.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::fmt;
+
+#[cfg(test)]
+mod tests;
+
+/// The accuracy of `FixedU128`, one unit of price.
+pub const ACCURACY: u128 = 1_000_000_000_000_000_000;
+
+/// Number of decimals of [`ACCURACY`].
+const DECIMALS: i64 = 18;
+
+/// Exponents are clamped to this magnitude, which is far beyond what fits in a `u128`.
+const MAX_EXPONENT: i64 = 1_000_000;
+
+/// Reasons a price fails to parse.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseError {
+	/// There are no digits.
+	Empty,
+	/// A character is not part of a decimal number.
+	InvalidCharacter,
+	/// The exponent after `e` has no digits.
+	InvalidExponent,
+	/// The price is below zero.
+	Negative,
+	/// The price does not fit in a `FixedU128`.
+	Overflow,
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let message = match self {
+			ParseError::Empty => "no digits",
+			ParseError::InvalidCharacter => "invalid character",
+			ParseError::InvalidExponent => "invalid exponent",
+			ParseError::Negative => "negative price",
+			ParseError::Overflow => "price overflows",
+		};
+		f.write_str(message)
+	}
+}
+
+/// Parse a decimal number such as `31.42`, `-0`, `.5`, `1.5e3` or `2E-4`, surrounded by
+/// optional whitespace, into the inner value of a `FixedU128`.
+pub fn parse(input: &str) -> Result<u128, ParseError> {
+	let input = input.trim().as_bytes();
+
+	let (negative, rest) = match input.split_first() {
+		Some((b'-', rest)) => (true, rest),
+		Some((b'+', rest)) => (false, rest),
+		_ => (false, input),
+	};
+
+	let (mantissa, exponent) = match rest.iter().position(|c| *c == b'e' || *c == b'E') {
+		Some(i) => (&rest[..i], parse_exponent(&rest[i + 1..])?),
+		None => (rest, 0),
+	};
+
+	let (integer, fraction) = match mantissa.iter().position(|c| *c == b'.') {
+		Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+		None => (mantissa, &[][..]),
+	};
+	if integer.is_empty() && fraction.is_empty() {
+		return Err(ParseError::Empty)
+	}
+	if !integer.iter().chain(fraction).all(u8::is_ascii_digit) {
+		return Err(ParseError::InvalidCharacter)
+	}
+
+	// leading zeros do not matter, skip them so that they do not count against the precision
+	let digits = integer.iter().chain(fraction).skip_while(|c| **c == b'0');
+	let len = digits.clone().count() as i64;
+	if len == 0 {
+		return Ok(0)
+	}
+	if negative {
+		return Err(ParseError::Negative)
+	}
+
+	// the price is `digits * 10^scale`, so the inner value is `digits * 10^(scale + DECIMALS)`
+	let scale = exponent - fraction.len() as i64 + DECIMALS;
+	if scale >= 0 {
+		let value = accumulate(digits)?;
+		let multiplier = 10u128.checked_pow(scale as u32).ok_or(ParseError::Overflow)?;
+		value.checked_mul(multiplier).ok_or(ParseError::Overflow)
+	} else {
+		// truncate the digits below the accuracy
+		let keep = len + scale;
+		if keep <= 0 {
+			return Ok(0)
+		}
+		accumulate(digits.take(keep as usize))
+	}
+}
+
+/// Parse a number decoded from e.g. JSON, such as an `f64` or a `u64`, through its shortest
+/// exponential form.
+pub fn parse_number<T: fmt::LowerExp>(value: &T) -> Result<u128, ParseError> {
+	let mut buffer = Buffer { bytes: [0; 64], len: 0 };
+	fmt::write(&mut buffer, format_args!("{:e}", value)).map_err(|_| ParseError::Overflow)?;
+	// the buffer only holds whole strings written by `fmt`, so it is valid utf-8
+	let text = core::str::from_utf8(&buffer.bytes[..buffer.len])
+		.map_err(|_| ParseError::InvalidCharacter)?;
+	parse(text)
+}
+
+fn parse_exponent(input: &[u8]) -> Result<i64, ParseError> {
+	let (negative, digits) = match input.split_first() {
+		Some((b'-', rest)) => (true, rest),
+		Some((b'+', rest)) => (false, rest),
+		_ => (false, input),
+	};
+	if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+		return Err(ParseError::InvalidExponent)
+	}
+	let magnitude = digits
+		.iter()
+		.fold(0i64, |acc, c| (acc * 10 + (c - b'0') as i64).min(MAX_EXPONENT));
+	Ok(if negative { -magnitude } else { magnitude })
+}
+
+fn accumulate<'a>(mut digits: impl Iterator<Item = &'a u8>) -> Result<u128, ParseError> {
+	digits.try_fold(0u128, |acc, c| {
+		acc.checked_mul(10)
+			.and_then(|v| v.checked_add((c - b'0') as u128))
+			.ok_or(ParseError::Overflow)
+	})
+}
+
+/// Fixed size writer for `parse_number`, exponential forms of numbers are short.
+struct Buffer {
+	bytes: [u8; 64],
+	len: usize,
+}
+
+impl fmt::Write for Buffer {
+	fn write_str(&mut self, s: &str) -> fmt::Result {
+		let end = self.len + s.len();
+		if end > self.bytes.len() {
+			return Err(fmt::Error)
+		}
+		self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+		self.len = end;
+		Ok(())
+	}
+}
@@ -0,0 +1,128 @@
+use super::*;
+use proptest::prelude::*;
+
+fn fixed(integer: u128, fraction: u128) -> u128 {
+	integer * ACCURACY + fraction
+}
+
+#[test]
+fn parse_decimals() {
+	assert_eq!(parse("31.42"), Ok(fixed(31, 420_000_000_000_000_000)));
+	assert_eq!(parse("31.4"), Ok(fixed(31, 400_000_000_000_000_000)));
+	assert_eq!(parse("7"), Ok(fixed(7, 0)));
+	assert_eq!(parse("7."), Ok(fixed(7, 0)));
+	assert_eq!(parse(".5"), Ok(fixed(0, 500_000_000_000_000_000)));
+	assert_eq!(parse("+0.000000000000000001"), Ok(1));
+	assert_eq!(parse(" 0012.50 "), Ok(fixed(12, 500_000_000_000_000_000)));
+}
+
+#[test]
+fn parse_truncates_beyond_accuracy() {
+	assert_eq!(parse("0.0000000000000000019"), Ok(1));
+	assert_eq!(parse("0.0000000000000000009"), Ok(0));
+	assert_eq!(
+		parse("1.123456789012345678999999"),
+		Ok(fixed(1, 123_456_789_012_345_678))
+	);
+}
+
+#[test]
+fn parse_exponents() {
+	assert_eq!(parse("1.5e3"), Ok(fixed(1500, 0)));
+	assert_eq!(parse("1.5E+3"), Ok(fixed(1500, 0)));
+	assert_eq!(parse("25e-1"), Ok(fixed(2, 500_000_000_000_000_000)));
+	assert_eq!(parse("1e-18"), Ok(1));
+	assert_eq!(parse("1e-19"), Ok(0));
+	assert_eq!(parse("0e999999999999"), Ok(0));
+	assert_eq!(parse("1e-999999999999"), Ok(0));
+}
+
+#[test]
+fn parse_zero_and_negative() {
+	assert_eq!(parse("0"), Ok(0));
+	assert_eq!(parse("-0.000"), Ok(0));
+	assert_eq!(parse("-1"), Err(ParseError::Negative));
+	assert_eq!(parse("-1e-30"), Err(ParseError::Negative));
+}
+
+#[test]
+fn parse_errors() {
+	assert_eq!(parse(""), Err(ParseError::Empty));
+	assert_eq!(parse(" "), Err(ParseError::Empty));
+	assert_eq!(parse("-"), Err(ParseError::Empty));
+	assert_eq!(parse("."), Err(ParseError::Empty));
+	assert_eq!(parse("e5"), Err(ParseError::Empty));
+	assert_eq!(parse("1e"), Err(ParseError::InvalidExponent));
+	assert_eq!(parse("1e+"), Err(ParseError::InvalidExponent));
+	assert_eq!(parse("1e1.5"), Err(ParseError::InvalidExponent));
+	assert_eq!(parse("1.2.3"), Err(ParseError::InvalidCharacter));
+	assert_eq!(parse("12,5"), Err(ParseError::InvalidCharacter));
+	assert_eq!(parse("NaN"), Err(ParseError::InvalidCharacter));
+	assert_eq!(parse("0x10"), Err(ParseError::InvalidCharacter));
+	assert_eq!(parse("--1"), Err(ParseError::InvalidCharacter));
+}
+
+#[test]
+fn parse_overflow() {
+	let max = u128::MAX / ACCURACY;
+	assert_eq!(parse(&max.to_string()), Ok(max * ACCURACY));
+	assert_eq!(parse(&(max + 1).to_string()), Err(ParseError::Overflow));
+	assert_eq!(parse("1e21"), Err(ParseError::Overflow));
+	assert_eq!(parse("1e999999999999"), Err(ParseError::Overflow));
+}
+
+#[test]
+fn parse_numbers() {
+	assert_eq!(parse_number(&31.42f64), Ok(fixed(31, 420_000_000_000_000_000)));
+	assert_eq!(parse_number(&7u64), Ok(fixed(7, 0)));
+	assert_eq!(parse_number(&5e-324f64), Ok(0));
+	assert_eq!(parse_number(&f64::MAX), Err(ParseError::Overflow));
+	assert_eq!(parse_number(&-2.5f64), Err(ParseError::Negative));
+	assert_eq!(parse_number(&f64::NAN), Err(ParseError::InvalidCharacter));
+}
+
+proptest! {
+	#[test]
+	fn never_panics(input in "\\PC*") {
+		let _ = parse(&input);
+	}
+
+	#[test]
+	fn decimal_matches_integer_and_fraction(integer in 0u64.., fraction in "[0-9]{0,40}") {
+		let text = format!("{}.{}", integer, fraction);
+		// the first 18 fractional digits, right padded with zeros
+		let padded = format!("{:0<18}", &fraction[..fraction.len().min(18)]);
+		let expected = integer as u128 * ACCURACY + padded.parse::<u128>().unwrap();
+		prop_assert_eq!(parse(&text), Ok(expected));
+	}
+
+	#[test]
+	fn exponent_moves_the_point(mantissa in 0u64..1_000_000, exponent in -30i32..=12) {
+		let text = format!("{}e{}", mantissa, exponent);
+		let scale = exponent + 18;
+		let expected = if scale >= 0 {
+			mantissa as u128 * 10u128.pow(scale as u32)
+		} else {
+			mantissa as u128 / 10u128.pow((-scale) as u32)
+		};
+		prop_assert_eq!(parse(&text), Ok(expected));
+	}
+
+	#[test]
+	fn exponent_form_equals_decimal_form(integer in 0u32.., fraction in "[0-9]{1,18}") {
+		let decimal = format!("{}.{}", integer, fraction);
+		let exponent = format!("{}{}e-{}", integer, fraction, fraction.len());
+		prop_assert_eq!(parse(&decimal), parse(&exponent));
+	}
+
+	#[test]
+	fn negative_prices_are_rejected(integer in 1u64.., fraction in "[0-9]{0,10}") {
+		prop_assert_eq!(parse(&format!("-{}.{}", integer, fraction)), Err(ParseError::Negative));
+	}
+
+	#[test]
+	fn floats_parse_close_to_their_value(value in 0f64..1e12) {
+		let parsed = parse_number(&value).unwrap() as f64 / ACCURACY as f64;
+		prop_assert!((parsed - value).abs() <= value * 1e-12 + 1e-18);
+	}
+}
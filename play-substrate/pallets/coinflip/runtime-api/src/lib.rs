@@ -0,0 +1,16 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runtime API of pallet-coinflip, to read the statistics of an account without a transaction.
+
+use codec::Codec;
+use pallet_coinflip::GameStats;
+
+sp_api::decl_runtime_apis! {
+	pub trait CoinflipApi<AccountId, Balance> where
+		AccountId: Codec,
+		Balance: Codec,
+	{
+		/// The games played, the total wagered and the total won by `who`.
+		fn stats(who: AccountId) -> GameStats<Balance>;
+	}
+}
@@ -0,0 +1,145 @@
+//! Benchmarking setup for pallet-coinflip
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as CoinFlip;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, Get, Hooks};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{One, Saturating, Zero};
+
+// fund the pot account with `pot`, on top of the existential deposit
+fn fund_pot<T: Config>(pot: BalanceOf<T>) {
+	Pot::<T>::put(pot);
+	T::Currency::make_free_balance_be(&CoinFlip::<T>::account_id(), pot.saturating_add(T::Currency::minimum_balance()));
+}
+
+// set the payment, fund the pot account and the caller, returns the caller
+fn funded_player<T: Config>() -> T::AccountId {
+	let payment = T::MinBet::get().saturating_mul(10u32.into());
+	Payment::<T>::put(payment);
+	fund_pot::<T>(payment);
+
+	let caller: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&caller, payment.saturating_mul(1000u32.into()));
+	caller
+}
+
+// play a game from block 1, returns the player and the block the game is revealed from
+fn player<T: Config>() -> (T::AccountId, T::BlockNumber) {
+	let caller = funded_player::<T>();
+	frame_system::Pallet::<T>::set_block_number(One::one());
+	let commitment = CoinFlip::<T>::commitment(&caller, true, &Default::default());
+	CoinFlip::<T>::play(RawOrigin::Signed(caller.clone()).into(), commitment).unwrap();
+	(caller, CoinFlip::<T>::reveal_at(One::one()))
+}
+
+// fund the account `index` and bet on the open round
+fn bettor<T: Config>(index: u32, heads: bool) -> T::AccountId {
+	let who: T::AccountId = account("bettor", index, 0);
+	T::Currency::make_free_balance_be(&who, T::MinBet::get().saturating_mul(1000u32.into()));
+	CoinFlip::<T>::bet(RawOrigin::Signed(who.clone()).into(), heads, T::MinBet::get()).unwrap();
+	who
+}
+
+benchmarks! {
+	play {
+		let caller = funded_player::<T>();
+		let commitment = CoinFlip::<T>::commitment(&caller, true, &Default::default());
+	}: _(RawOrigin::Signed(caller.clone()), commitment)
+	verify {
+		assert!(Games::<T>::contains_key(&caller));
+	}
+
+	reveal {
+		let (caller, reveal_at) = player::<T>();
+		frame_system::Pallet::<T>::set_block_number(reveal_at);
+		CoinFlip::<T>::on_initialize(reveal_at);
+		// the winner is paid out
+		let salt = Default::default();
+		let guess = CoinFlip::<T>::flip(&Draws::<T>::get(reveal_at).unwrap().seed.unwrap(), &salt);
+		Games::<T>::mutate(&caller, |game| {
+			if let Some(game) = game {
+				game.commitment = CoinFlip::<T>::commitment(&caller, guess, &salt);
+			}
+		});
+	}: _(RawOrigin::Signed(caller.clone()), guess, salt)
+	verify {
+		assert!(!Games::<T>::contains_key(&caller));
+	}
+
+	settle {
+		let (player, reveal_at) = player::<T>();
+		frame_system::Pallet::<T>::set_block_number(reveal_at.saturating_add(T::RevealPeriod::get()));
+		let caller: T::AccountId = account("caller", 0, 0);
+	}: _(RawOrigin::Signed(caller), player.clone())
+	verify {
+		assert!(!Games::<T>::contains_key(&player));
+	}
+
+	fund {
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, T::MinBet::get().saturating_mul(1000u32.into()));
+		let amount = T::MinBet::get().saturating_mul(100u32.into());
+	}: _(RawOrigin::Signed(caller), amount)
+	verify {
+		assert!(!Pot::<T>::get().is_zero());
+	}
+
+	bet {
+		fund_pot::<T>(Zero::zero());
+		// a round with bets on both sides
+		bettor::<T>(0, true);
+		bettor::<T>(1, false);
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, T::MinBet::get().saturating_mul(1000u32.into()));
+		let stake = T::MinBet::get().saturating_mul(10u32.into());
+	}: _(RawOrigin::Signed(caller.clone()), true, stake)
+	verify {
+		assert_eq!(Bets::<T>::get(0, &caller), Some(Bet { heads: true, stake }));
+	}
+
+	close_round {
+		fund_pot::<T>(Zero::zero());
+		bettor::<T>(0, true);
+		bettor::<T>(1, false);
+	}: { CoinFlip::<T>::close_round(0) }
+	verify {
+		assert!(Rounds::<T>::get(0).unwrap().outcome.is_some());
+		assert_eq!(OpenRound::<T>::get(), None);
+	}
+
+	settle_bets {
+		let n in 1 .. T::MaxPayoutsPerBlock::get();
+		fund_pot::<T>(Zero::zero());
+		// every bet is paid out, whatever the outcome, as a round on one side is refunded
+		for i in 0 .. n {
+			bettor::<T>(i, true);
+		}
+		CoinFlip::<T>::close_round(0);
+	}: { CoinFlip::<T>::settle_bets(n) }
+	verify {
+		assert_eq!(Rounds::<T>::get(0), None);
+		assert_eq!(NextSettlement::<T>::get(), 1);
+	}
+
+	set_loss_limit {
+		// a looser limit is pending
+		let caller: T::AccountId = whitelisted_caller();
+		CoinFlip::<T>::set_loss_limit(RawOrigin::Signed(caller.clone()).into(), Some(T::MinBet::get())).unwrap();
+	}: _(RawOrigin::Signed(caller.clone()), None)
+	verify {
+		assert!(AccountLimits::<T>::get(&caller).pending.is_some());
+	}
+
+	cool_off {
+		let caller: T::AccountId = whitelisted_caller();
+		let blocks = T::LimitPeriod::get();
+	}: _(RawOrigin::Signed(caller.clone()), blocks)
+	verify {
+		assert!(AccountLimits::<T>::get(&caller).cool_off_until >= blocks);
+	}
+}
+
+impl_benchmark_test_suite!(CoinFlip, crate::mock::new_test_ext(), crate::mock::Test);
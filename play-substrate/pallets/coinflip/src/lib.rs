@@ -1,6 +1,32 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 /// A FRAME pallet for coin flip game
+///
+/// A game has two phases, so that neither block authors nor observers of the transaction pool
+/// can predict the outcome when the stake is paid:
+/// 1. `play` commits the hash of the guess and a secret salt, and pays the stake.
+/// 2. `reveal`, from `RevealDelay` blocks later, discloses the guess and the salt. The outcome
+///    is derived from the randomness drawn in the block `RevealDelay` blocks after `play`,
+///    combined with the salt, so that it does not depend on the block the game is revealed in.
+///
+/// A game which is not revealed within `RevealPeriod` blocks is lost, and can be settled by
+/// anyone with `settle`.
+///
+/// Several players can also bet on a round, see `bet`. The round closes `RoundDuration` blocks
+/// after the first bet, one outcome is drawn, and the winners split the losing side pro rata.
+/// The bets of the closed rounds are paid out in order, at most `MaxPayoutsPerBlock` per block.
+///
+/// Games and rounds are numbered apart, and the events carry these numbers: a game is identified
+/// by the `Nonce` it is played with, a round by its `RoundIndex`, counted by `RoundCount`. The
+/// round indices have no gaps, as the settlement queue walks the rounds one by one from
+/// `NextSettlement`, which the games taking numbers out of the same counter would break.
+///
+/// The pot, and the bets of the games and rounds until they are settled, are held by the account
+/// derived from `PalletId`. The pot is funded by transfers with `fund`, the pallet does not mint.
+///
+/// Each account can limit its net loss per `LimitPeriod` blocks with `set_loss_limit`, and stop
+/// playing for a while with `cool_off`. Both apply to `play` and `bet`, along with the statistics
+/// of the account, see `stats`.
 
 pub use pallet::*;
 
@@ -10,67 +36,349 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migrations;
+pub mod weights;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
 	use frame_system::pallet_prelude::*;
-	use frame_support::traits::{Currency, WithdrawReasons, ExistenceRequirement, Randomness};
-	use sp_runtime::traits::{Zero, Hash, Saturating};
+	use frame_support::{
+		traits::{Currency, ExistenceRequirement, Randomness, StorageVersion},
+		PalletId,
+	};
+	use sp_runtime::{
+		helpers_128bit::multiply_by_rational,
+		traits::{AccountIdConversion, One, Zero, Hash, SaturatedConversion, Saturating},
+		Permill,
+	};
+	use scale_info::TypeInfo;
+	pub use crate::weights::WeightInfo;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	/// Version 1 holds the pot in the pot account, see `migrations::v1`.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
-	// TODO: refactor to inject currency trait
 	#[pallet::config]
-	pub trait Config: frame_system::Config + pallet_balances::Config {
+	pub trait Config: frame_system::Config {
 		type Randomness: Randomness<Self::Hash, Self::BlockNumber>;
 
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The currency the game is played with. It is only minted by `migrations::v1`.
+		type Currency: Currency<Self::AccountId>;
+
+		/// The id of the pot account.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Number of blocks after `play` from which the game can be revealed, at least 1. The coin
+		/// of the game is flipped with the randomness of that block.
+		#[pallet::constant]
+		type RevealDelay: Get<Self::BlockNumber>;
+
+		/// Number of blocks a game can be revealed in, it is lost afterwards.
+		#[pallet::constant]
+		type RevealPeriod: Get<Self::BlockNumber>;
+
+		/// The part of each stake, and of the losing side of each round, going to the pot.
+		#[pallet::constant]
+		type HouseEdge: Get<Permill>;
+
+		/// Number of blocks a round takes bets for, from its first bet.
+		#[pallet::constant]
+		type RoundDuration: Get<Self::BlockNumber>;
+
+		/// The minimum stake of a bet on a round.
+		#[pallet::constant]
+		type MinBet: Get<BalanceOf<Self>>;
+
+		/// Maximum number of bets paid out in a block.
+		#[pallet::constant]
+		type MaxPayoutsPerBlock: Get<u32>;
+
+		/// Number of blocks the daily loss limits apply to, and a looser limit takes to apply.
+		#[pallet::constant]
+		type LimitPeriod: Get<Self::BlockNumber>;
+
+		/// Information on runtime weights.
+		type WeightInfo: WeightInfo;
+	}
+
+	pub type RoundIndex = u32;
+
+	/// A committed game.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct Game<Hash, Balance, BlockNumber> {
+		/// The nonce the game was played with, which identifies it in the events.
+		pub id: u64,
+		/// Hash of the player, the guess and the salt, see `commitment`.
+		pub commitment: Hash,
+		/// The amount bet by the player, matched by the pot. The winner takes twice of it.
+		pub bet: Balance,
+		/// The block the game was committed in.
+		pub played_at: BlockNumber,
+	}
+
+	/// The randomness drawn in a block for the games revealed from it.
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct Draw<Hash> {
+		/// The randomness of the block, drawn in its `on_initialize`.
+		pub seed: Option<Hash>,
+		/// Number of the unsettled games revealed with it.
+		pub games: u32,
+	}
+
+	/// A betting round.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct Round<Balance, BlockNumber> {
+		/// The block the round stops taking bets and is drawn in.
+		pub closes_at: BlockNumber,
+		/// Total stake on heads.
+		pub heads: Balance,
+		/// Total stake on tails.
+		pub tails: Balance,
+		/// `true` for heads, once the round is drawn.
+		pub outcome: Option<bool>,
+		/// The stakes held by the round which are not paid out yet.
+		pub unsettled: Balance,
+	}
+
+	/// A bet on a round.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct Bet<Balance> {
+		/// `true` for heads.
+		pub heads: bool,
+		pub stake: Balance,
+	}
+
+	/// The statistics of an account.
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct GameStats<Balance> {
+		/// Number of games played and bets placed.
+		pub played: u32,
+		/// Total of the payments and stakes.
+		pub wagered: Balance,
+		/// Total paid out to the account, refunds included.
+		pub won: Balance,
+	}
+
+	/// The limits an account sets on itself.
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct Limits<Balance, BlockNumber> {
+		/// The net loss allowed per `LimitPeriod`, none for no limit.
+		pub loss_limit: Option<Balance>,
+		/// A looser loss limit, and the block it applies from.
+		pub pending: Option<(Option<Balance>, BlockNumber)>,
+		/// The account cannot play before this block.
+		pub cool_off_until: BlockNumber,
+	}
+
+	impl<Balance: Copy, BlockNumber: PartialOrd> Limits<Balance, BlockNumber> {
+		/// The loss limit in force at `now`.
+		pub fn loss_limit_at(&self, now: &BlockNumber) -> Option<Balance> {
+			match self.pending {
+				Some((limit, ref from)) if now >= from => limit,
+				_ => self.loss_limit,
+			}
+		}
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::storage]
 	#[pallet::getter(fn payment)]
-	pub type Payment<T: Config> = StorageValue<_, T::Balance>;
+	pub type Payment<T: Config> = StorageValue<_, BalanceOf<T>>;
 
+	/// The funds of the pot account which are not held by unsettled games.
 	#[pallet::storage]
 	#[pallet::getter(fn pot)]
-	pub type Pot<T: Config> = StorageValue<_, T::Balance, ValueQuery>;
+	pub type Pot<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+	/// The id of the next game.
 	#[pallet::storage]
 	#[pallet::getter(fn nonce)]
 	pub type Nonce<T> = StorageValue<_, u64, ValueQuery>;
 
+	/// The unsettled game of each player.
+	#[pallet::storage]
+	#[pallet::getter(fn games)]
+	pub type Games<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Game<T::Hash, BalanceOf<T>, T::BlockNumber>,
+	>;
+
+	/// The draws of the games, by the block the games can be revealed from.
+	#[pallet::storage]
+	#[pallet::getter(fn draws)]
+	pub type Draws<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Draw<T::Hash>>;
+
+	/// Number of rounds ever opened, the index of the next round.
+	#[pallet::storage]
+	#[pallet::getter(fn round_count)]
+	pub type RoundCount<T> = StorageValue<_, RoundIndex, ValueQuery>;
+
+	/// The round taking bets.
+	#[pallet::storage]
+	#[pallet::getter(fn open_round)]
+	pub type OpenRound<T> = StorageValue<_, RoundIndex>;
+
+	/// The rounds which are not settled yet.
+	#[pallet::storage]
+	#[pallet::getter(fn rounds)]
+	pub type Rounds<T: Config> =
+		StorageMap<_, Twox64Concat, RoundIndex, Round<BalanceOf<T>, T::BlockNumber>>;
+
+	/// The bets of each round which are not paid out yet.
+	#[pallet::storage]
+	#[pallet::getter(fn bets)]
+	pub type Bets<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		RoundIndex,
+		Blake2_128Concat,
+		T::AccountId,
+		Bet<BalanceOf<T>>,
+	>;
+
+	/// The settlement queue: the oldest round whose bets are not all paid out. The rounds from
+	/// it until the open round are settled in order once they are drawn.
+	#[pallet::storage]
+	#[pallet::getter(fn next_settlement)]
+	pub type NextSettlement<T> = StorageValue<_, RoundIndex, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn stats)]
+	pub type Stats<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, GameStats<BalanceOf<T>>, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn limits)]
+	pub type AccountLimits<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Limits<BalanceOf<T>, T::BlockNumber>, ValueQuery>;
+
+	/// The net loss of each account in its last period played, as `(period, loss)`. The period
+	/// is the block number divided by `LimitPeriod`.
+	#[pallet::storage]
+	#[pallet::getter(fn losses)]
+	pub type Losses<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, (T::BlockNumber, BalanceOf<T>)>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		PaymentSet(T::Balance),
-		PlayResult(T::AccountId, T::Balance),
+		PaymentSet(BalanceOf<T>),
+		/// \[who, amount\]
+		PotFunded(T::AccountId, BalanceOf<T>),
+		/// \[game, player, commitment\]
+		GamePlayed(u64, T::AccountId, T::Hash),
+		/// \[game, player, winnings\]
+		PlayResult(u64, T::AccountId, BalanceOf<T>),
+		/// An unrevealed game was settled as lost. \[game, player\]
+		GameForfeited(u64, T::AccountId),
+		/// \[round, closes_at\]
+		RoundOpened(RoundIndex, T::BlockNumber),
+		/// \[round, who, heads, stake\]
+		BetPlaced(RoundIndex, T::AccountId, bool, BalanceOf<T>),
+		/// \[round, heads\]
+		RoundClosed(RoundIndex, bool),
+		/// \[round, who, payout\]
+		BetSettled(RoundIndex, T::AccountId, BalanceOf<T>),
+		/// All the bets of the round are paid out. \[round\]
+		RoundSettled(RoundIndex),
+		/// The loss limit applies from the block, none for no limit. \[who, limit, from\]
+		LossLimitSet(T::AccountId, Option<BalanceOf<T>>, T::BlockNumber),
+		/// \[who, until\]
+		CoolingOff(T::AccountId, T::BlockNumber),
 	}
 
 	#[pallet::error]
 	pub enum Error<T> {
 		NonePaymentValue,
+		/// The player has not settled the previous game yet.
+		GameInProgress,
+		/// The pot cannot match the bet.
+		InsufficientPot,
+		/// The pot account does not exist yet, see `fund`.
+		PotNotFunded,
+		NoGame,
+		/// The game cannot be revealed before `RevealDelay` blocks.
+		RevealTooEarly,
+		/// The game was not revealed within `RevealPeriod` blocks.
+		RevealExpired,
+		/// The guess and salt do not match the commitment.
+		InvalidReveal,
+		/// The game can still be revealed by the player.
+		GameNotExpired,
+		/// The stake is below `MinBet`.
+		BetTooSmall,
+		/// The account has already bet on the round.
+		AlreadyBet,
+		/// The account is cooling off.
+		CoolingOff,
+		/// The wager would exceed the loss limit of the account.
+		LossLimitExceeded,
 	}
 
 	#[pallet::hooks]
-	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let mut weight = T::DbWeight::get().reads(2);
+			if let Some(draw) = Self::draws(now) {
+				if draw.seed.is_none() {
+					let (seed, _) = T::Randomness::random(&(b"coinflip/reveal", now).encode());
+					<Draws<T>>::insert(now, Draw { seed: Some(seed), ..draw });
+					weight = weight.saturating_add(T::DbWeight::get().writes(1));
+				}
+			}
+
+			if let Some(index) = Self::open_round() {
+				weight = weight.saturating_add(T::DbWeight::get().reads(1));
+				if Self::rounds(index).map_or(false, |round| round.closes_at <= now) {
+					Self::close_round(index);
+					weight = weight.saturating_add(T::WeightInfo::close_round());
+				}
+			}
+
+			let settled = Self::settle_bets(T::MaxPayoutsPerBlock::get());
+			weight.saturating_add(T::WeightInfo::settle_bets(settled))
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() < 1 {
+				return crate::migrations::v1::migrate::<T>();
+			}
+			0
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			ensure!(StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION, "coinflip storage is not migrated");
+			Self::check_pot()
+		}
+	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		/// Start the game by initialize the storage items.
+		/// Start the game by initialize the storage items, root only. The pot is funded with `fund`.
 		#[pallet::weight(0)]
-		pub fn set_payment(origin: OriginFor<T>, value: T::Balance) -> DispatchResultWithPostInfo {
-			// Ensure the function call is a signed message (i.e. a transaction)
-			ensure_signed(origin)?;
+		pub fn set_payment(origin: OriginFor<T>, value: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			ensure_root(origin)?;
 
 			// If `payment` is not initialized with some value, set the payment
 			if Self::payment().is_none() {
 				// set input value to the payment
 				<Payment<T>>::put(value);
-				
-				// Initialize jackpot;
-				<Pot<T>>::put(value);
 
 				// Raise an event for the set payment
 				Self::deposit_event(Event::PaymentSet(value));
@@ -78,57 +386,407 @@ pub mod pallet {
 
 			Ok(().into())
 		}
-		
-		/// This function allow a user to play our coin flip game
-		#[pallet::weight(0)]
-		pub fn play(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+
+		/// Transfer `amount` into the pot, by anyone. The existential deposit of the pot account is
+		/// kept out of the pot when the account is created, so that paying out never reaps it.
+		#[pallet::weight(T::WeightInfo::fund())]
+		pub fn fund(origin: OriginFor<T>, amount: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let account = Self::account_id();
+			let deposit = if T::Currency::total_balance(&account).is_zero() {
+				T::Currency::minimum_balance()
+			} else {
+				Zero::zero()
+			};
+			T::Currency::transfer(&sender, &account, amount, ExistenceRequirement::KeepAlive)?;
+			<Pot<T>>::mutate(|pot| *pot = pot.saturating_add(amount.saturating_sub(deposit)));
+
+			Self::deposit_event(Event::PotFunded(sender, amount));
+
+			Ok(().into())
+		}
+
+		/// Play a game by committing to a guess, see `commitment`, and paying the stake
+		#[pallet::weight(T::WeightInfo::play())]
+		pub fn play(origin: OriginFor<T>, commitment: T::Hash) -> DispatchResultWithPostInfo {
 			// Ensure that the function call is a signed message (i.e. a transaction)
 			let sender = ensure_signed(origin)?;
 
 			// Ensure the payment storage item has been set
 			let payment = Self::payment().ok_or(Error::<T>::NonePaymentValue)?;
+			ensure!(!<Games<T>>::contains_key(&sender), Error::<T>::GameInProgress);
+
+			// The house edge of the stake goes to the pot, which matches the rest of it
+			let edge = T::HouseEdge::get().mul_floor(payment);
+			let bet = payment.saturating_sub(edge);
+			let pot = Self::pot().saturating_add(edge);
+			ensure!(pot >= bet, Error::<T>::InsufficientPot);
+			let loss = Self::ensure_can_wager(&sender, payment)?;
+
+			// Try to pay into the pot account, making sure that it will not kill the account
+			T::Currency::transfer(&sender, &Self::account_id(), payment, ExistenceRequirement::KeepAlive)?;
+			Self::note_wager(&sender, payment, loss);
+
+			// The matched bet is held by the game until it is settled
+			<Pot<T>>::put(pot - bet);
+			let id = Self::nonce();
+			let played_at = <frame_system::Pallet<T>>::block_number();
+			<Games<T>>::insert(&sender, Game { id, commitment, bet, played_at });
+			<Draws<T>>::mutate(Self::reveal_at(played_at), |draw| {
+				let draw = draw.get_or_insert_with(Default::default);
+				draw.games = draw.games.saturating_add(1);
+			});
+			Nonce::<T>::put(id.wrapping_add(1));
 
-			// Read our storage values, and place them in memory variables
-			let mut nonce = Self::nonce();
-			let mut pot = Self::pot();
+			Self::deposit_event(Event::GamePlayed(id, sender, commitment));
+
+			Ok(().into())
+		}
+
+		/// Reveal the guess and the salt of the game, and pay the winnings
+		#[pallet::weight(T::WeightInfo::reveal())]
+		pub fn reveal(origin: OriginFor<T>, guess: bool, salt: T::Hash) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
 
-			// Try to withdraw the payment from the account, making sure that it will not kill the account
-			let _ = <pallet_balances::Pallet<T> as Currency<_>>::withdraw(&sender, payment, WithdrawReasons::RESERVE.into(), ExistenceRequirement::KeepAlive)?;
+			let game = Self::games(&sender).ok_or(Error::<T>::NoGame)?;
+			let now = <frame_system::Pallet<T>>::block_number();
+			let reveal_at = Self::reveal_at(game.played_at);
+			ensure!(now >= reveal_at, Error::<T>::RevealTooEarly);
+			ensure!(now < reveal_at.saturating_add(T::RevealPeriod::get()), Error::<T>::RevealExpired);
+			ensure!(Self::commitment(&sender, guess, &salt) == game.commitment, Error::<T>::InvalidReveal);
 
+			// The randomness of the block `RevealDelay` after the play was not known when the guess
+			// was committed, and does not change with the block the game is revealed in
+			let random = Self::draws(reveal_at).and_then(|draw| draw.seed).ok_or(Error::<T>::RevealTooEarly)?;
+			let prize = game.bet.saturating_add(game.bet);
 			let mut winnings = Zero::zero();
 
-			// Generate a random seed using randomness_collective_flip pallet
-			let random_seed = T::Randomness::random_seed().0.using_encoded(T::Hashing::hash);
-			let seed_arr = random_seed.as_ref();
-		
-			// as_ref returns an array of u8
-			if seed_arr[seed_arr.len() - 1] < 128 {
-				// If the user won the coin flip, deposit the pot winnings; cannot fail
-				let _ = <pallet_balances::Pallet<T> as Currency<_>>::deposit_into_existing(&sender, pot)
-					.expect("`sender` must exist since a transaction is being make and withdraw will keep alive; qed.");
-				
+			if Self::flip(&random, &salt) == guess {
+				// The pot account holds the prize on top of its existential deposit
+				T::Currency::transfer(&Self::account_id(), &sender, prize, ExistenceRequirement::KeepAlive)?;
+				Self::note_win(&sender, prize);
+
 				// Set the winnings
-				winnings = pot;
+				winnings = prize;
+			} else {
+				<Pot<T>>::mutate(|pot| *pot = pot.saturating_add(prize));
+			}
+
+			<Games<T>>::remove(&sender);
+			Self::release_draw(reveal_at);
+
+			// Raise event for the play result
+			Self::deposit_event(Event::PlayResult(game.id, sender, winnings));
+
+			Ok(().into())
+		}
+
+		/// Bet `stake` on heads, or on tails, in the open round. The first bet opens a round.
+		#[pallet::weight(T::WeightInfo::bet())]
+		pub fn bet(origin: OriginFor<T>, heads: bool, stake: BalanceOf<T>) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+			ensure!(stake >= T::MinBet::get(), Error::<T>::BetTooSmall);
 
-				// Reduce the pot to zero
-				pot = Zero::zero();
+			let open = Self::open_round().and_then(|index| Self::rounds(index).map(|round| (index, round)));
+			let opened = open.is_none();
+			let (index, mut round) = open.unwrap_or_else(|| (Self::round_count(), Round {
+				closes_at: <frame_system::Pallet<T>>::block_number().saturating_add(T::RoundDuration::get()),
+				heads: Zero::zero(),
+				tails: Zero::zero(),
+				outcome: None,
+				unsettled: Zero::zero(),
+			}));
+			ensure!(!<Bets<T>>::contains_key(index, &sender), Error::<T>::AlreadyBet);
+			// The pot account is kept alive to pay out every stake
+			ensure!(!T::Currency::total_balance(&Self::account_id()).is_zero(), Error::<T>::PotNotFunded);
+			let loss = Self::ensure_can_wager(&sender, stake)?;
+
+			T::Currency::transfer(&sender, &Self::account_id(), stake, ExistenceRequirement::KeepAlive)?;
+			Self::note_wager(&sender, stake, loss);
+
+			if opened {
+				<OpenRound<T>>::put(index);
+				<RoundCount<T>>::put(index.saturating_add(1));
+				Self::deposit_event(Event::RoundOpened(index, round.closes_at));
+			}
+			if heads {
+				round.heads = round.heads.saturating_add(stake);
+			} else {
+				round.tails = round.tails.saturating_add(stake);
 			}
+			round.unsettled = round.unsettled.saturating_add(stake);
+			<Rounds<T>>::insert(index, round);
+			<Bets<T>>::insert(index, &sender, Bet { heads, stake });
+
+			Self::deposit_event(Event::BetPlaced(index, sender, heads, stake));
+
+			Ok(().into())
+		}
 
-			// No matter the outcome, increase the pot by the payment amount
-			pot = pot.saturating_add(payment);
+		/// Limit the net loss of the account per `LimitPeriod` blocks, or remove the limit with
+		/// `None`. A tighter limit applies at once, a looser one after `LimitPeriod` blocks.
+		#[pallet::weight(T::WeightInfo::set_loss_limit())]
+		pub fn set_loss_limit(origin: OriginFor<T>, limit: Option<BalanceOf<T>>) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
 
-			// Increase the nonce
-			nonce = nonce.wrapping_add(1);
+			let now = <frame_system::Pallet<T>>::block_number();
+			let mut limits = Self::limits(&sender);
+			let current = limits.loss_limit_at(&now);
+			let tighter = match (limit, current) {
+				(_, None) => true,
+				(Some(limit), Some(current)) => limit <= current,
+				(None, Some(_)) => false,
+			};
 
-			// Store the updated value for our storage items
-			<Pot<T>>::put(pot);
-			Nonce::<T>::put(nonce);
+			let from = if tighter {
+				limits.loss_limit = limit;
+				limits.pending = None;
+				now
+			} else {
+				let from = now.saturating_add(T::LimitPeriod::get());
+				limits.loss_limit = current;
+				limits.pending = Some((limit, from));
+				from
+			};
+			<AccountLimits<T>>::insert(&sender, limits);
 
-			// Raise event for the play result
-			Self::deposit_event(Event::PlayResult(sender, winnings));
+			Self::deposit_event(Event::LossLimitSet(sender, limit, from));
+
+			Ok(().into())
+		}
+
+		/// Stop the account from playing for `blocks` blocks. A cool-off cannot be shortened.
+		#[pallet::weight(T::WeightInfo::cool_off())]
+		pub fn cool_off(origin: OriginFor<T>, blocks: T::BlockNumber) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let until = <frame_system::Pallet<T>>::block_number().saturating_add(blocks);
+			let until = <AccountLimits<T>>::mutate(&sender, |limits| {
+				if until > limits.cool_off_until {
+					limits.cool_off_until = until;
+				}
+				limits.cool_off_until
+			});
+
+			Self::deposit_event(Event::CoolingOff(sender, until));
+
+			Ok(().into())
+		}
+
+		/// Settle a game which was not revealed in time as lost, by anyone
+		#[pallet::weight(T::WeightInfo::settle())]
+		pub fn settle(origin: OriginFor<T>, player: T::AccountId) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			let game = Self::games(&player).ok_or(Error::<T>::NoGame)?;
+			let reveal_at = Self::reveal_at(game.played_at);
+			let expiry = reveal_at.saturating_add(T::RevealPeriod::get());
+			ensure!(<frame_system::Pallet<T>>::block_number() >= expiry, Error::<T>::GameNotExpired);
+
+			<Pot<T>>::mutate(|pot| *pot = pot.saturating_add(game.bet).saturating_add(game.bet));
+			<Games<T>>::remove(&player);
+			Self::release_draw(reveal_at);
+
+			Self::deposit_event(Event::GameForfeited(game.id, player));
 
 			Ok(().into())
 		}
 	}
 
-}
\ No newline at end of file
+	impl<T: Config> Pallet<T> {
+		/// The account holding the pot and the bets of the unsettled games.
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account()
+		}
+
+		/// The block a game played at `played_at` can be revealed from, whose randomness flips its
+		/// coin. It is after the play block, as the randomness is drawn when the block initializes.
+		pub fn reveal_at(played_at: T::BlockNumber) -> T::BlockNumber {
+			played_at.saturating_add(T::RevealDelay::get().max(One::one()))
+		}
+
+		/// Release the draw of a settled game, the draw is removed with its last game.
+		fn release_draw(reveal_at: T::BlockNumber) {
+			<Draws<T>>::mutate_exists(reveal_at, |draw| {
+				if let Some(current) = draw {
+					current.games = current.games.saturating_sub(1);
+					if current.games == 0 {
+						*draw = None;
+					}
+				}
+			});
+		}
+
+		/// The sum of the bets matched by the pot, which are held by the unsettled games, and of the
+		/// stakes held by the unsettled rounds.
+		pub fn held() -> BalanceOf<T> {
+			let games = <Games<T>>::iter_values()
+				.fold(Zero::zero(), |held: BalanceOf<T>, game| held.saturating_add(game.bet).saturating_add(game.bet));
+			<Rounds<T>>::iter_values().fold(games, |held, round| held.saturating_add(round.unsettled))
+		}
+
+		/// Check that the pot account holds the pot and the bets of the unsettled games and rounds,
+		/// on top of its existential deposit.
+		pub fn check_pot() -> Result<(), &'static str> {
+			let tracked = Self::pot().saturating_add(Self::held());
+			if tracked.is_zero() {
+				return Ok(());
+			}
+			let required = tracked.saturating_add(T::Currency::minimum_balance());
+			ensure!(T::Currency::free_balance(&Self::account_id()) >= required, "pot account does not hold the pot");
+			Ok(())
+		}
+
+		/// The commitment `player` plays with, `guess` is `true` for heads. The player is part of
+		/// it so that others cannot copy the commitment and reveal it after the player.
+		pub fn commitment(player: &T::AccountId, guess: bool, salt: &T::Hash) -> T::Hash {
+			(player, guess, salt).using_encoded(T::Hashing::hash)
+		}
+
+		/// Flip the coin with `random` and `salt`, `true` for heads.
+		pub fn flip(random: &T::Hash, salt: &T::Hash) -> bool {
+			Self::is_heads(&(random, salt).using_encoded(T::Hashing::hash))
+		}
+
+		/// Whether `seed` flips the coin to heads.
+		pub fn is_heads(seed: &T::Hash) -> bool {
+			let seed_arr = seed.as_ref();
+			seed_arr[seed_arr.len() - 1] < 128
+		}
+
+		/// The net loss of `who` in the current period, should `amount` be lost. Fails when the
+		/// account is cooling off, or the loss would exceed its limit.
+		pub(crate) fn ensure_can_wager(
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> Result<(T::BlockNumber, BalanceOf<T>), DispatchError> {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let limits = Self::limits(who);
+			ensure!(now >= limits.cool_off_until, Error::<T>::CoolingOff);
+
+			let period = now / T::LimitPeriod::get().max(One::one());
+			let loss = match Self::losses(who) {
+				Some((last, loss)) if last == period => loss,
+				_ => Zero::zero(),
+			}.saturating_add(amount);
+			if let Some(limit) = limits.loss_limit_at(&now) {
+				ensure!(loss <= limit, Error::<T>::LossLimitExceeded);
+			}
+			Ok((period, loss))
+		}
+
+		/// Record the wager of `amount` by `who`, with the period and loss from `ensure_can_wager`.
+		pub(crate) fn note_wager(who: &T::AccountId, amount: BalanceOf<T>, loss: (T::BlockNumber, BalanceOf<T>)) {
+			<Stats<T>>::mutate(who, |stats| {
+				stats.played = stats.played.saturating_add(1);
+				stats.wagered = stats.wagered.saturating_add(amount);
+			});
+			<Losses<T>>::insert(who, loss);
+		}
+
+		/// Record the payout of `amount` to `who`, which offsets its loss of the current period.
+		pub(crate) fn note_win(who: &T::AccountId, amount: BalanceOf<T>) {
+			<Stats<T>>::mutate(who, |stats| stats.won = stats.won.saturating_add(amount));
+
+			let period = <frame_system::Pallet<T>>::block_number() / T::LimitPeriod::get().max(One::one());
+			<Losses<T>>::mutate(who, |losses| {
+				if let Some((last, loss)) = losses {
+					if *last == period {
+						*loss = loss.saturating_sub(amount);
+					}
+				}
+			});
+		}
+
+		/// Draw the outcome of the round `index` and queue its bets for settlement.
+		pub(crate) fn close_round(index: RoundIndex) {
+			let mut round = match Self::rounds(index) {
+				Some(round) => round,
+				None => return,
+			};
+			let (random, _) = T::Randomness::random(&(b"coinflip/round", index).encode());
+			let heads = Self::is_heads(&random);
+
+			// The house edge of the losing side goes to the pot, unless the bets are refunded
+			let (winning, losing) = if heads { (round.heads, round.tails) } else { (round.tails, round.heads) };
+			if !winning.is_zero() && !losing.is_zero() {
+				let edge = T::HouseEdge::get().mul_floor(losing);
+				round.unsettled = round.unsettled.saturating_sub(edge);
+				<Pot<T>>::mutate(|pot| *pot = pot.saturating_add(edge));
+			}
+
+			round.outcome = Some(heads);
+			<Rounds<T>>::insert(index, round);
+			<OpenRound<T>>::kill();
+			Self::deposit_event(Event::RoundClosed(index, heads));
+		}
+
+		/// The payout of `bet` in the drawn `round`. Winners take back their stake and a share of
+		/// the losing side pro rata, the bets are refunded when a side has no stake.
+		pub fn payout(round: &Round<BalanceOf<T>, T::BlockNumber>, bet: &Bet<BalanceOf<T>>) -> BalanceOf<T> {
+			let heads = match round.outcome {
+				Some(heads) => heads,
+				None => return Zero::zero(),
+			};
+			let (winning, losing) = if heads { (round.heads, round.tails) } else { (round.tails, round.heads) };
+			if winning.is_zero() || losing.is_zero() {
+				return bet.stake;
+			}
+			if bet.heads != heads {
+				return Zero::zero();
+			}
+
+			let prize = losing.saturating_sub(T::HouseEdge::get().mul_floor(losing));
+			// the share is at most `prize` as the stake is at most `winning`
+			let share = multiply_by_rational(
+				prize.saturated_into(),
+				bet.stake.saturated_into(),
+				winning.saturated_into(),
+			).map_or_else(|_| Zero::zero(), |share| share.saturated_into());
+			bet.stake.saturating_add(share)
+		}
+
+		/// Pay out at most `max` bets of the drawn rounds in order, returns the number paid out.
+		pub(crate) fn settle_bets(max: u32) -> u32 {
+			let account = Self::account_id();
+			let mut settled = 0;
+
+			while settled < max {
+				let index = Self::next_settlement();
+				let mut round = match Self::rounds(index) {
+					Some(round) if round.outcome.is_some() => round,
+					_ => break,
+				};
+
+				for (who, bet) in <Bets<T>>::drain_prefix(index).take((max - settled) as usize) {
+					let mut payout = Self::payout(&round, &bet);
+					round.unsettled = round.unsettled.saturating_sub(payout);
+					// e.g. a payout below the existential deposit of a reaped account goes to the pot
+					if !payout.is_zero() && T::Currency::transfer(&account, &who, payout, ExistenceRequirement::KeepAlive).is_err() {
+						round.unsettled = round.unsettled.saturating_add(payout);
+						payout = Zero::zero();
+					}
+					if !payout.is_zero() {
+						Self::note_win(&who, payout);
+					}
+					settled += 1;
+					Self::deposit_event(Event::BetSettled(index, who, payout));
+				}
+
+				if <Bets<T>>::iter_prefix(index).next().is_some() {
+					<Rounds<T>>::insert(index, round);
+					break;
+				}
+				// What is left over is the rounding of the payouts
+				<Pot<T>>::mutate(|pot| *pot = pot.saturating_add(round.unsettled));
+				<Rounds<T>>::remove(index);
+				<NextSettlement<T>>::put(index.saturating_add(1));
+				Self::deposit_event(Event::RoundSettled(index));
+			}
+
+			settled
+		}
+	}
+
+}
@@ -0,0 +1,39 @@
+//! Storage migrations of the coinflip pallet.
+
+use crate::{Config, Pallet, Payment, Pot};
+use frame_support::{
+	traits::{Currency, Get, Imbalance, StorageVersion},
+	weights::Weight,
+};
+use sp_runtime::traits::{Saturating, Zero};
+
+/// Version 0 burnt the payments and minted the winnings, `Pot` was only a number, and had no
+/// games in storage. Version 1 holds the pot in the pot account.
+pub mod v1 {
+	use super::*;
+
+	/// Fund the pot account with `Pot`. The version is left at 0 when the deposit fails, so that
+	/// the migration runs again on the next upgrade.
+	pub fn migrate<T: Config>() -> Weight {
+		let pot = Pot::<T>::get();
+
+		// the pot account is created once the payment is set, the pot which version 0 only counted
+		// is minted into it, along with the existential deposit so that paying out never reaps it
+		if !pot.is_zero() || Payment::<T>::exists() {
+			let account = Pallet::<T>::account_id();
+			let funds = if T::Currency::total_balance(&account).is_zero() {
+				pot.saturating_add(T::Currency::minimum_balance())
+			} else {
+				pot
+			};
+			if T::Currency::deposit_creating(&account, funds).peek() < funds {
+				log::error!("coinflip: cannot fund the pot account with {:?}, the migration will run again", funds);
+				return T::DbWeight::get().reads_writes(5, 0);
+			}
+		}
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		// the deposit reads and writes the pot account and the total issuance
+		T::DbWeight::get().reads_writes(5, 3)
+	}
+}
@@ -2,11 +2,13 @@
 
 use crate as pallet_coinflip;
 use sp_core::H256;
-use frame_support::{parameter_types, traits::Randomness};
+use codec::Encode;
+use frame_support::{parameter_types, traits::Randomness, PalletId};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+	traits::{BlakeTwo256, Hash, IdentityLookup}, testing::Header, Permill,
 };
 use frame_system as system;
+use std::cell::RefCell;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -72,20 +74,52 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
-impl Randomness<<Test as system::Config>::Hash, <Test as system::Config>::BlockNumber> for CoinFlipModule {
+thread_local! {
+	static RANDOM_SEED: RefCell<H256> = RefCell::new(H256::zero());
+}
+
+/// Randomness of the current block, see `random_at`, whatever the subject.
+pub struct TestRandomness;
+
+impl Randomness<<Test as system::Config>::Hash, <Test as system::Config>::BlockNumber> for TestRandomness {
 	fn random(_subject: &[u8]) -> (<Test as system::Config>::Hash, <Test as system::Config>::BlockNumber) {
-		let output = match Self::nonce()  {
-			0 => H256::from_low_u64_be(101),
-			1 => H256::from_low_u64_be(150),
-			_ => panic!("do not use other value except 1,2 for nonce in test cases"),
-		};
-		(output, 0)
+		(random_at(System::block_number()), System::block_number())
 	}
 }
 
+/// The randomness of block `n`, which changes with the block and the seed set by `set_random_seed`.
+pub fn random_at(n: u64) -> H256 {
+	RANDOM_SEED.with(|seed| (*seed.borrow(), n).using_encoded(BlakeTwo256::hash))
+}
+
+pub fn set_random_seed(seed: H256) {
+	RANDOM_SEED.with(|current| *current.borrow_mut() = seed);
+}
+
+parameter_types! {
+	pub const RevealDelay: u64 = 2;
+	pub const RevealPeriod: u64 = 5;
+	pub const HouseEdge: Permill = Permill::from_percent(10);
+	pub const CoinflipPalletId: PalletId = PalletId(*b"py/cflip");
+	pub const RoundDuration: u64 = 5;
+	pub const MinBet: u64 = 2;
+	pub const MaxPayoutsPerBlock: u32 = 2;
+	pub const LimitPeriod: u64 = 10;
+}
+
 impl pallet_coinflip::Config for Test {
-	type Randomness = CoinFlipModule;
+	type Randomness = TestRandomness;
 	type Event = Event;
+	type Currency = Balances;
+	type PalletId = CoinflipPalletId;
+	type RevealDelay = RevealDelay;
+	type RevealPeriod = RevealPeriod;
+	type HouseEdge = HouseEdge;
+	type RoundDuration = RoundDuration;
+	type MinBet = MinBet;
+	type MaxPayoutsPerBlock = MaxPayoutsPerBlock;
+	type LimitPeriod = LimitPeriod;
+	type WeightInfo = ();
 }
 
 // pub type System = frame_system::Pallet<Test>;
@@ -100,8 +134,13 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 		balances: vec![
 			(1, 10),
 			(2, 20),
+			(3, 100),
+			(4, 100),
 		]
 	}.assimilate_storage(&mut t).unwrap();
-	
-	t.into()
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	// events are not recorded in the genesis block
+	ext.execute_with(|| System::set_block_number(1));
+	ext
 }
@@ -2,25 +2,76 @@
 
 use crate::{Error, mock::*};
 use frame_support::{assert_ok, assert_noop};
-use frame_support::traits::Currency;
+use frame_support::traits::{Currency, Hooks, StorageVersion};
+use sp_core::H256;
 use sp_runtime::traits::BadOrigin;
 use super::*;
 use pallet_balances::Error as BalancesError;
 
+fn salt() -> H256 {
+	H256::repeat_byte(7)
+}
+
+fn commitment(guess: bool) -> H256 {
+	CoinFlipModule::commitment(&2, guess, &salt())
+}
+
+// set the payment and fund the pot account with the pot, on top of the existential deposit
+fn start(payment: u64, pot: u64) {
+	<Payment<Test>>::put(payment);
+	<Pot<Test>>::put(pot);
+	Balances::make_free_balance_be(&CoinFlipModule::account_id(), pot + 1);
+}
+
+fn pot_account_balance() -> u64 {
+	Balances::free_balance(&CoinFlipModule::account_id())
+}
+
+fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		<CoinFlipModule as Hooks<u64>>::on_initialize(System::block_number());
+	}
+}
+
 #[test]
 fn set_payment_should_work() {
 	new_test_ext().execute_with(|| {
+		// only root sets the payment
+		assert_noop!(CoinFlipModule::set_payment(Origin::signed(1), 100), BadOrigin);
+
 		// asserting the function can be called successfully
-		assert_ok!(CoinFlipModule::set_payment(Origin::signed(1), 100));
+		assert_ok!(CoinFlipModule::set_payment(Origin::root(), 100));
 		// asserting that the stored value is equal to what we stored
         assert_eq!(CoinFlipModule::payment(), Some(100));
-		assert_eq!(CoinFlipModule::pot(), 100);
-		
+		// nothing is minted into the pot
+		assert_eq!(CoinFlipModule::pot(), 0);
+		assert_eq!(pot_account_balance(), 0);
+
 		// do not update the stored value
-		assert_ok!(CoinFlipModule::set_payment(Origin::signed(1), 200));
+		assert_ok!(CoinFlipModule::set_payment(Origin::root(), 200));
 		// asserting that the stored value is equal to what we stored
         assert_eq!(CoinFlipModule::payment(), Some(100));
-        assert_eq!(CoinFlipModule::pot(), 100);
+	});
+}
+
+#[test]
+fn fund_should_transfer_into_the_pot() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(CoinFlipModule::fund(Origin::root(), 30), BadOrigin);
+		assert_noop!(CoinFlipModule::fund(Origin::signed(1), 20), BalancesError::<Test, _>::InsufficientBalance);
+
+		// the existential deposit of the new pot account is kept out of the pot
+		assert_ok!(CoinFlipModule::fund(Origin::signed(3), 31));
+		assert_eq!(CoinFlipModule::pot(), 30);
+		System::assert_last_event(crate::Event::<Test>::PotFunded(3, 31).into());
+
+		assert_ok!(CoinFlipModule::fund(Origin::signed(4), 10));
+		assert_eq!(CoinFlipModule::pot(), 40);
+		assert_eq!(Balances::total_balance(&3), 69);
+		assert_eq!(Balances::total_balance(&4), 90);
+		assert_eq!(pot_account_balance(), 41);
+		assert_ok!(CoinFlipModule::check_pot());
 	});
 }
 
@@ -28,53 +79,418 @@ fn set_payment_should_work() {
 fn play_security_check_should_work() {
 	new_test_ext().execute_with(|| {
 		// Test ensure_signed
-		assert_noop!(CoinFlipModule::play(Origin::root()), BadOrigin);
+		assert_noop!(CoinFlipModule::play(Origin::root(), commitment(true)), BadOrigin);
 
 		// Ensure the correct error if payment not set
-		assert_noop!(CoinFlipModule::play(Origin::signed(2)), Error::<Test>::NonePaymentValue);
+		assert_noop!(CoinFlipModule::play(Origin::signed(2), commitment(true)), Error::<Test>::NonePaymentValue);
 
 		// Check the balances in genesis config
-		 assert_eq!(Balances::total_balance(&2), 20);
+		assert_eq!(Balances::total_balance(&2), 20);
 
-		 // set payment and pot, higher than the balances
-		 <Payment<Test>>::put(30);
-		 <Pot<Test>>::put(30);
+		// set payment and pot, higher than the balances
+		start(30, 30);
+		assert_noop!(CoinFlipModule::play(Origin::signed(2), commitment(true)), BalancesError::<Test, _>::InsufficientBalance);
 
-		 assert_noop!(CoinFlipModule::play(Origin::signed(2)), BalancesError::<Test, _>::InsufficientBalance);
+		// the pot with the house edge of 1 cannot match a bet of 9
+		start(10, 7);
+		assert_noop!(CoinFlipModule::play(Origin::signed(2), commitment(true)), Error::<Test>::InsufficientPot);
 
-		 // set payment and pot, lower than the balances
-		 <Payment<Test>>::put(10);
-		 <Pot<Test>>::put(10);
-		 assert_ok!(CoinFlipModule::play(Origin::signed(2)));
+		// set payment and pot, lower than the balances
+		start(10, 10);
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
+
+		// one game at a time
+		assert_noop!(CoinFlipModule::play(Origin::signed(2), commitment(false)), Error::<Test>::GameInProgress);
+	})
+}
+
+#[test]
+fn play_should_hold_the_bet() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
+		// 1 of the payment is the house edge, the bet of 9 is matched by the pot
+		assert_eq!(CoinFlipModule::pot(), 22); // 30 + 1 - 9
+		assert_eq!(Balances::total_balance(&2), 10); // 20 - 10 (payment)
+		assert_eq!(CoinFlipModule::games(2), Some(Game { id: 0, commitment: commitment(true), bet: 9, played_at: 1 }));
+		assert_eq!(CoinFlipModule::nonce(), 1);
+		// the pot account holds the payment
+		assert_eq!(pot_account_balance(), 41);
+		assert_ok!(CoinFlipModule::check_pot());
+		System::assert_last_event(crate::Event::<Test>::GamePlayed(0, 2, commitment(true)).into());
+	})
+}
+
+#[test]
+fn reveal_security_check_should_work() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+		assert_noop!(CoinFlipModule::reveal(Origin::signed(2), true, salt()), Error::<Test>::NoGame);
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
+
+		// the game is revealed 2 blocks later
+		run_to_block(2);
+		assert_noop!(CoinFlipModule::reveal(Origin::signed(2), true, salt()), Error::<Test>::RevealTooEarly);
+
+		run_to_block(3);
+		assert_noop!(CoinFlipModule::reveal(Origin::signed(2), false, salt()), Error::<Test>::InvalidReveal);
+		assert_noop!(CoinFlipModule::reveal(Origin::signed(2), true, H256::zero()), Error::<Test>::InvalidReveal);
+
+		// within 5 blocks
+		run_to_block(8);
+		assert_noop!(CoinFlipModule::reveal(Origin::signed(2), true, salt()), Error::<Test>::RevealExpired);
 	})
 }
 
 #[test]
 fn play_should_work_for_win() {
 	new_test_ext().execute_with(|| {
-		<Payment<Test>>::put(10);
-		<Pot<Test>>::put(30);
-		<Nonce<Test>>::put(0);
+		start(10, 30);
 
-		assert_ok!(CoinFlipModule::play(Origin::signed(2)));
+		// the coin is flipped with the randomness of block 3
+		let guess = CoinFlipModule::flip(&random_at(3), &salt());
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(guess)));
+		assert_eq!(CoinFlipModule::draws(3), Some(Draw { seed: None, games: 1 }));
+
+		run_to_block(3);
+		assert_eq!(CoinFlipModule::draws(3), Some(Draw { seed: Some(random_at(3)), games: 1 }));
+		assert_ok!(CoinFlipModule::reveal(Origin::signed(2), guess, salt()));
+		assert_eq!(CoinFlipModule::draws(3), None);
 		assert_eq!(CoinFlipModule::payment(), Some(10));
-		assert_eq!(CoinFlipModule::pot(), 10);
-		assert_eq!(Balances::total_balance(&2), 40); // 20 - 10 (payment) + 30 (reward)
-		assert_eq!(CoinFlipModule::nonce(), 1);
+		assert_eq!(CoinFlipModule::pot(), 22);
+		assert_eq!(Balances::total_balance(&2), 28); // 20 - 10 (payment) + 18 (reward)
+		assert_eq!(pot_account_balance(), 23); // 31 + 10 (payment) - 18 (reward)
+		assert_eq!(CoinFlipModule::games(2), None);
+		assert_ok!(CoinFlipModule::check_pot());
+		System::assert_last_event(crate::Event::<Test>::PlayResult(0, 2, 18).into());
 	})
 }
 
 #[test]
 fn play_should_work_for_lose() {
 	new_test_ext().execute_with(|| {
-		<Payment<Test>>::put(10);
-		<Pot<Test>>::put(30);
-		<Nonce<Test>>::put(1);
+		start(10, 30);
+
+		let guess = !CoinFlipModule::flip(&random_at(3), &salt());
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(guess)));
 
-		assert_ok!(CoinFlipModule::play(Origin::signed(2)));
+		run_to_block(3);
+		assert_ok!(CoinFlipModule::reveal(Origin::signed(2), guess, salt()));
 		assert_eq!(CoinFlipModule::payment(), Some(10));
-		assert_eq!(CoinFlipModule::pot(), 40);
+		assert_eq!(CoinFlipModule::pot(), 40); // 22 + 18 (bet)
 		assert_eq!(Balances::total_balance(&2), 10); // 20 - 10 (payment)
+		assert_eq!(pot_account_balance(), 41);
+		assert_eq!(CoinFlipModule::games(2), None);
+		assert_ok!(CoinFlipModule::check_pot());
+		System::assert_last_event(crate::Event::<Test>::PlayResult(0, 2, 0).into());
+	})
+}
+
+#[test]
+fn delayed_reveal_should_not_change_the_outcome() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		// a seed flipping the coin one way at block 3, and the other way at block 5
+		let seed = (0..).map(H256::from_low_u64_be).find(|seed| {
+			set_random_seed(*seed);
+			CoinFlipModule::flip(&random_at(3), &salt()) != CoinFlipModule::flip(&random_at(5), &salt())
+		}).unwrap();
+		set_random_seed(seed);
+
+		// the game is won with the randomness of block 3
+		let guess = CoinFlipModule::flip(&random_at(3), &salt());
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(guess)));
+
+		// and revealing it in block 5 does not change the outcome
+		run_to_block(5);
+		assert_ok!(CoinFlipModule::reveal(Origin::signed(2), guess, salt()));
+		assert_eq!(Balances::total_balance(&2), 28);
+		System::assert_last_event(crate::Event::<Test>::PlayResult(0, 2, 18).into());
+	})
+}
+
+#[test]
+fn unrevealed_game_can_be_settled_by_anyone() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+		assert_noop!(CoinFlipModule::settle(Origin::signed(1), 2), Error::<Test>::NoGame);
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
+
+		// the player can reveal until block 7
+		System::set_block_number(7);
+		assert_noop!(CoinFlipModule::settle(Origin::signed(1), 2), Error::<Test>::GameNotExpired);
+
+		System::set_block_number(8);
+		assert_noop!(CoinFlipModule::settle(Origin::root(), 2), BadOrigin);
+		assert_ok!(CoinFlipModule::settle(Origin::signed(1), 2));
+		assert_eq!(CoinFlipModule::pot(), 40); // 22 + 18 (bet)
+		assert_eq!(Balances::total_balance(&2), 10);
+		assert_eq!(pot_account_balance(), 41);
+		assert_eq!(CoinFlipModule::games(2), None);
+		assert_eq!(CoinFlipModule::draws(3), None);
+		assert_ok!(CoinFlipModule::check_pot());
+		System::assert_last_event(crate::Event::<Test>::GameForfeited(0, 2).into());
+	})
+}
+
+#[test]
+fn check_pot_should_detect_missing_funds() {
+	new_test_ext().execute_with(|| {
+		// nothing to hold yet
+		assert_ok!(CoinFlipModule::check_pot());
+
+		start(10, 30);
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
+		assert_ok!(CoinFlipModule::check_pot());
+
+		// the pot of 22 and the held bet of 18 are missing 1
+		Balances::make_free_balance_be(&CoinFlipModule::account_id(), 40);
+		assert!(CoinFlipModule::check_pot().is_err());
+	})
+}
+
+#[test]
+fn migration_should_fund_the_pot_account() {
+	new_test_ext().execute_with(|| {
+		// version 0 kept the pot as a number
+		StorageVersion::new(0).put::<CoinFlipModule>();
+		<Payment<Test>>::put(10);
+		<Pot<Test>>::put(30);
+		assert_eq!(pot_account_balance(), 0);
+
+		<CoinFlipModule as Hooks<u64>>::on_runtime_upgrade();
+		assert_eq!(pot_account_balance(), 31); // 30 + 1 (existential deposit)
+		assert_eq!(StorageVersion::get::<CoinFlipModule>(), 1);
+		assert_ok!(CoinFlipModule::check_pot());
+
+		// the game is paid out of the pot account
+		let guess = CoinFlipModule::flip(&random_at(3), &salt());
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(guess)));
+		run_to_block(3);
+		assert_ok!(CoinFlipModule::reveal(Origin::signed(2), guess, salt()));
+		assert_eq!(pot_account_balance(), 23); // 31 + 10 (payment) - 18 (reward)
+
+		// and the migration runs once
+		<CoinFlipModule as Hooks<u64>>::on_runtime_upgrade();
+		assert_eq!(pot_account_balance(), 23);
+	})
+}
+
+#[test]
+fn bet_security_check_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(CoinFlipModule::bet(Origin::root(), true, 5), BadOrigin);
+		assert_noop!(CoinFlipModule::bet(Origin::signed(1), true, 1), Error::<Test>::BetTooSmall);
+		assert_noop!(CoinFlipModule::bet(Origin::signed(1), true, 5), Error::<Test>::PotNotFunded);
+
+		start(10, 30);
+		assert_noop!(CoinFlipModule::bet(Origin::signed(1), true, 20), BalancesError::<Test, _>::InsufficientBalance);
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(1), true, 5));
+		// one bet per round
+		assert_noop!(CoinFlipModule::bet(Origin::signed(1), false, 2), Error::<Test>::AlreadyBet);
+	})
+}
+
+#[test]
+fn bet_should_open_a_round() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(1), true, 5));
+		System::assert_has_event(crate::Event::<Test>::RoundOpened(0, 6).into());
+		System::assert_last_event(crate::Event::<Test>::BetPlaced(0, 1, true, 5).into());
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(2), false, 10));
+		assert_eq!(CoinFlipModule::open_round(), Some(0));
+		assert_eq!(CoinFlipModule::round_count(), 1);
+		assert_eq!(CoinFlipModule::rounds(0), Some(Round { closes_at: 6, heads: 5, tails: 10, outcome: None, unsettled: 15 }));
+		assert_eq!(CoinFlipModule::bets(0, 2), Some(Bet { heads: false, stake: 10 }));
+		// the pot account holds the stakes
+		assert_eq!(pot_account_balance(), 46);
+		assert_ok!(CoinFlipModule::check_pot());
+	})
+}
+
+#[test]
+fn round_should_pay_the_winners_pro_rata() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+		// a seed drawing heads when the round closes at block 6
+		let seed = (0..).map(H256::from_low_u64_be).find(|seed| {
+			set_random_seed(*seed);
+			CoinFlipModule::is_heads(&random_at(6))
+		}).unwrap();
+		set_random_seed(seed);
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(1), true, 5));
+		assert_ok!(CoinFlipModule::bet(Origin::signed(2), false, 10));
+		assert_ok!(CoinFlipModule::bet(Origin::signed(3), true, 15));
+		assert_ok!(CoinFlipModule::bet(Origin::signed(4), false, 20));
+
+		run_to_block(5);
+		assert_eq!(CoinFlipModule::open_round(), Some(0));
+
+		// the round closes at block 6, and 2 bets are paid out per block
+		run_to_block(6);
+		System::assert_has_event(crate::Event::<Test>::RoundClosed(0, true).into());
+		assert_eq!(CoinFlipModule::open_round(), None);
+		assert_eq!(Bets::<Test>::iter_prefix(0).count(), 2);
+		assert_ok!(CoinFlipModule::check_pot());
+
+		run_to_block(7);
+		System::assert_last_event(crate::Event::<Test>::RoundSettled(0).into());
+		assert_eq!(CoinFlipModule::rounds(0), None);
+		assert_eq!(CoinFlipModule::next_settlement(), 1);
+
+		// the house edge of 3 goes to the pot, the winners split 27 pro rata to their stakes
+		assert_eq!(Balances::total_balance(&1), 16); // 10 - 5 + 11
+		assert_eq!(Balances::total_balance(&2), 10);
+		assert_eq!(Balances::total_balance(&3), 120); // 100 - 15 + 35
+		assert_eq!(Balances::total_balance(&4), 80);
+		System::assert_has_event(crate::Event::<Test>::BetSettled(0, 2, 0).into());
+		System::assert_has_event(crate::Event::<Test>::BetSettled(0, 3, 35).into());
+		// along with the rounding left over
+		assert_eq!(CoinFlipModule::pot(), 34);
+		assert_eq!(pot_account_balance(), 35);
+		assert_ok!(CoinFlipModule::check_pot());
+	})
+}
+
+#[test]
+fn one_sided_round_should_be_refunded() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(1), true, 5));
+		assert_ok!(CoinFlipModule::bet(Origin::signed(3), true, 15));
+
+		run_to_block(6);
+		System::assert_last_event(crate::Event::<Test>::RoundSettled(0).into());
+		assert_eq!(Balances::total_balance(&1), 10);
+		assert_eq!(Balances::total_balance(&3), 100);
+		assert_eq!(CoinFlipModule::pot(), 30);
+		assert_eq!(pot_account_balance(), 31);
+	})
+}
+
+#[test]
+fn bet_after_close_should_open_the_next_round() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(1), true, 5));
+		run_to_block(6);
+
+		assert_ok!(CoinFlipModule::bet(Origin::signed(1), false, 2));
+		System::assert_has_event(crate::Event::<Test>::RoundOpened(1, 11).into());
+		assert_eq!(CoinFlipModule::open_round(), Some(1));
+		assert_eq!(CoinFlipModule::round_count(), 2);
+	})
+}
+
+#[test]
+fn games_should_be_identified_by_nonce() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
+		let commitment_3 = CoinFlipModule::commitment(&3, true, &salt());
+		assert_ok!(CoinFlipModule::play(Origin::signed(3), commitment_3));
+		System::assert_last_event(crate::Event::<Test>::GamePlayed(1, 3, commitment_3).into());
+		assert_eq!(CoinFlipModule::games(3).map(|game| game.id), Some(1));
 		assert_eq!(CoinFlipModule::nonce(), 2);
+
+		System::set_block_number(8);
+		assert_ok!(CoinFlipModule::settle(Origin::signed(1), 3));
+		System::assert_last_event(crate::Event::<Test>::GameForfeited(1, 3).into());
+	})
+}
+
+#[test]
+fn stats_should_count_games_and_bets() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+
+		let guess = CoinFlipModule::flip(&random_at(3), &salt());
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(guess)));
+		assert_eq!(CoinFlipModule::stats(2), GameStats { played: 1, wagered: 10, won: 0 });
+
+		run_to_block(3);
+		assert_ok!(CoinFlipModule::reveal(Origin::signed(2), guess, salt()));
+		assert_eq!(CoinFlipModule::stats(2), GameStats { played: 1, wagered: 10, won: 18 });
+
+		// a refunded bet is paid out too
+		assert_ok!(CoinFlipModule::bet(Origin::signed(2), true, 5));
+		run_to_block(8);
+		assert_eq!(CoinFlipModule::stats(2), GameStats { played: 2, wagered: 15, won: 23 });
+		assert_eq!(CoinFlipModule::stats(1), GameStats::default());
+	})
+}
+
+#[test]
+fn loss_limit_should_be_enforced() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+		assert_ok!(CoinFlipModule::set_loss_limit(Origin::signed(3), Some(15)));
+		System::assert_last_event(crate::Event::<Test>::LossLimitSet(3, Some(15), 1).into());
+
+		assert_ok!(CoinFlipModule::play(Origin::signed(3), CoinFlipModule::commitment(&3, true, &salt())));
+		assert_noop!(CoinFlipModule::bet(Origin::signed(3), true, 6), Error::<Test>::LossLimitExceeded);
+		assert_ok!(CoinFlipModule::bet(Origin::signed(3), true, 5));
+		assert_eq!(CoinFlipModule::losses(3), Some((0, 15)));
+
+		// the refund of the bet offsets the loss
+		run_to_block(6);
+		assert_eq!(CoinFlipModule::losses(3), Some((0, 10)));
+
+		// the game is lost, and the loss is counted per 10 blocks
+		run_to_block(8);
+		assert_ok!(CoinFlipModule::settle(Origin::signed(1), 3));
+		let play = || CoinFlipModule::play(Origin::signed(3), CoinFlipModule::commitment(&3, false, &salt()));
+		assert_noop!(play(), Error::<Test>::LossLimitExceeded);
+
+		run_to_block(10);
+		assert_ok!(play());
+		assert_eq!(CoinFlipModule::losses(3), Some((1, 10)));
+	})
+}
+
+#[test]
+fn looser_loss_limit_should_apply_later() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(CoinFlipModule::set_loss_limit(Origin::signed(3), Some(15)));
+
+		// removing the limit takes a period
+		assert_ok!(CoinFlipModule::set_loss_limit(Origin::signed(3), None));
+		System::assert_last_event(crate::Event::<Test>::LossLimitSet(3, None, 11).into());
+		assert_eq!(CoinFlipModule::limits(3).loss_limit_at(&10), Some(15));
+		assert_eq!(CoinFlipModule::limits(3).loss_limit_at(&11), None);
+
+		// while a tighter limit applies at once
+		assert_ok!(CoinFlipModule::set_loss_limit(Origin::signed(3), Some(12)));
+		System::assert_last_event(crate::Event::<Test>::LossLimitSet(3, Some(12), 1).into());
+		assert_eq!(CoinFlipModule::limits(3), Limits { loss_limit: Some(12), pending: None, cool_off_until: 0 });
+	})
+}
+
+#[test]
+fn cool_off_should_stop_playing() {
+	new_test_ext().execute_with(|| {
+		start(10, 30);
+		assert_ok!(CoinFlipModule::cool_off(Origin::signed(2), 5));
+		System::assert_last_event(crate::Event::<Test>::CoolingOff(2, 6).into());
+
+		// and cannot be shortened
+		assert_ok!(CoinFlipModule::cool_off(Origin::signed(2), 1));
+		System::assert_last_event(crate::Event::<Test>::CoolingOff(2, 6).into());
+
+		assert_noop!(CoinFlipModule::play(Origin::signed(2), commitment(true)), Error::<Test>::CoolingOff);
+		assert_noop!(CoinFlipModule::bet(Origin::signed(2), true, 5), Error::<Test>::CoolingOff);
+
+		System::set_block_number(6);
+		assert_ok!(CoinFlipModule::play(Origin::signed(2), commitment(true)));
 	})
 }
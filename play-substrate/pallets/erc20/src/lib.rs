@@ -0,0 +1,275 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// A FRAME pallet of a native asset with the semantics of the ink! erc20 contract.
+///
+/// The pallet implements `fungibles::{Inspect, Mutate, Transfer}` with `()` as the only asset
+/// id, so it can be plugged into other pallets wherever a fungible asset is expected. Pallets
+/// taking a single `fungible` currency can use it through `fungible::ItemOf<Erc20, AssetId,
+/// AccountId>` where `AssetId` returns `()`.
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+	use frame_support::traits::tokens::{
+		fungibles::{Inspect, Mutate, Transfer},
+		DepositConsequence, WithdrawConsequence,
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::{AtLeast32BitUnsigned, CheckedAdd, CheckedSub, MaybeSerializeDeserialize, Zero};
+	use sp_std::prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The balance type of the asset.
+		type Balance: Parameter
+			+ Member
+			+ AtLeast32BitUnsigned
+			+ Default
+			+ Copy
+			+ MaybeSerializeDeserialize
+			+ MaxEncodedLen;
+
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	#[pallet::getter(fn total_supply)]
+	pub type TotalSupply<T: Config> = StorageValue<_, T::Balance, ValueQuery>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn balance_of)]
+	pub type Balances<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, T::Balance, ValueQuery>;
+
+	/// Amount `spender` is still allowed to withdraw from `owner`, keyed by owner then spender.
+	#[pallet::storage]
+	#[pallet::getter(fn allowance)]
+	pub type Allowances<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		T::AccountId,
+		T::Balance,
+		ValueQuery,
+	>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub balances: Vec<(T::AccountId, T::Balance)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { balances: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (who, value) in self.balances.iter() {
+				Pallet::<T>::do_mint(who, *value).expect("genesis balances overflow the total supply");
+			}
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Tokens are moved from one account to another. `None` stands for minting or burning.
+		/// \[from, to, value\]
+		Transfer(Option<T::AccountId>, Option<T::AccountId>, T::Balance),
+		/// An allowance is set. \[owner, spender, value\]
+		Approval(T::AccountId, T::AccountId, T::Balance),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// Not enough balance.
+		InsufficientBalance,
+		/// Not enough allowance.
+		InsufficientApproval,
+		/// The balance or total supply would overflow.
+		Overflow,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Transfer `value` tokens from the caller to `to`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn transfer(origin: OriginFor<T>, to: T::AccountId, value: T::Balance) -> DispatchResultWithPostInfo {
+			let from = ensure_signed(origin)?;
+
+			Self::do_transfer(&from, &to, value)?;
+
+			Ok(().into())
+		}
+
+		/// Allow `spender` to withdraw up to `value` tokens from the caller, replacing the
+		/// current allowance.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn approve(origin: OriginFor<T>, spender: T::AccountId, value: T::Balance) -> DispatchResultWithPostInfo {
+			let owner = ensure_signed(origin)?;
+
+			<Allowances<T>>::insert(&owner, &spender, value);
+
+			Self::deposit_event(Event::Approval(owner, spender, value));
+
+			Ok(().into())
+		}
+
+		/// Transfer `value` tokens from `from` to `to` on behalf of the caller, using up the
+		/// allowance `from` gave to the caller.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		pub fn transfer_from(
+			origin: OriginFor<T>,
+			from: T::AccountId,
+			to: T::AccountId,
+			value: T::Balance,
+		) -> DispatchResultWithPostInfo {
+			let spender = ensure_signed(origin)?;
+
+			let allowance = Self::allowance(&from, &spender);
+			let remaining = allowance.checked_sub(&value).ok_or(Error::<T>::InsufficientApproval)?;
+
+			Self::do_transfer(&from, &to, value)?;
+			<Allowances<T>>::insert(&from, &spender, remaining);
+
+			Ok(().into())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Move `value` tokens from `from` to `to`. Storage is only written once all checks
+		/// have passed; a transfer to oneself leaves the balance untouched.
+		pub fn do_transfer(from: &T::AccountId, to: &T::AccountId, value: T::Balance) -> DispatchResult {
+			let from_balance = Self::balance_of(from);
+			let new_from_balance = from_balance.checked_sub(&value).ok_or(Error::<T>::InsufficientBalance)?;
+
+			if from != to {
+				let new_to_balance = Self::balance_of(to).checked_add(&value).ok_or(Error::<T>::Overflow)?;
+				Self::set_balance(from, new_from_balance);
+				Self::set_balance(to, new_to_balance);
+			}
+
+			Self::deposit_event(Event::Transfer(Some(from.clone()), Some(to.clone()), value));
+
+			Ok(())
+		}
+
+		/// Create `value` tokens on `who`, increasing the total supply.
+		pub fn do_mint(who: &T::AccountId, value: T::Balance) -> DispatchResult {
+			let total_supply = Self::total_supply().checked_add(&value).ok_or(Error::<T>::Overflow)?;
+			// cannot overflow, the balance is bounded by the total supply
+			let balance = Self::balance_of(who) + value;
+
+			<TotalSupply<T>>::put(total_supply);
+			Self::set_balance(who, balance);
+
+			Self::deposit_event(Event::Transfer(None, Some(who.clone()), value));
+
+			Ok(())
+		}
+
+		/// Destroy `value` tokens of `who`, decreasing the total supply.
+		pub fn do_burn(who: &T::AccountId, value: T::Balance) -> DispatchResult {
+			let balance = Self::balance_of(who).checked_sub(&value).ok_or(Error::<T>::InsufficientBalance)?;
+
+			<TotalSupply<T>>::mutate(|total| *total -= value);
+			Self::set_balance(who, balance);
+
+			Self::deposit_event(Event::Transfer(Some(who.clone()), None, value));
+
+			Ok(())
+		}
+
+		/// Store the balance of `who`, an emptied account is removed.
+		fn set_balance(who: &T::AccountId, balance: T::Balance) {
+			if balance.is_zero() {
+				<Balances<T>>::remove(who);
+			} else {
+				<Balances<T>>::insert(who, balance);
+			}
+		}
+	}
+
+	/// The asset has no existential deposit on purpose, like the erc20 contract: `minimum_balance`
+	/// is zero and `keep_alive` is ignored, so the whole balance is reducible and a withdrawal never
+	/// fails with `WouldDie` or `ReducedToZero`. An account is removed once its balance is zero.
+	impl<T: Config> Inspect<T::AccountId> for Pallet<T> {
+		type AssetId = ();
+		type Balance = T::Balance;
+
+		fn total_issuance(_asset: ()) -> T::Balance {
+			Self::total_supply()
+		}
+
+		fn minimum_balance(_asset: ()) -> T::Balance {
+			Zero::zero()
+		}
+
+		fn balance(_asset: (), who: &T::AccountId) -> T::Balance {
+			Self::balance_of(who)
+		}
+
+		fn reducible_balance(_asset: (), who: &T::AccountId, _keep_alive: bool) -> T::Balance {
+			// there is no existential deposit, the whole balance can be moved
+			Self::balance_of(who)
+		}
+
+		fn can_deposit(_asset: (), _who: &T::AccountId, amount: T::Balance) -> DepositConsequence {
+			match Self::total_supply().checked_add(&amount) {
+				Some(_) => DepositConsequence::Success,
+				None => DepositConsequence::Overflow,
+			}
+		}
+
+		fn can_withdraw(_asset: (), who: &T::AccountId, amount: T::Balance) -> WithdrawConsequence<T::Balance> {
+			if Self::balance_of(who) < amount {
+				WithdrawConsequence::NoFunds
+			} else {
+				WithdrawConsequence::Success
+			}
+		}
+	}
+
+	impl<T: Config> Mutate<T::AccountId> for Pallet<T> {
+		fn mint_into(_asset: (), who: &T::AccountId, amount: T::Balance) -> DispatchResult {
+			Self::do_mint(who, amount)
+		}
+
+		fn burn_from(_asset: (), who: &T::AccountId, amount: T::Balance) -> Result<T::Balance, DispatchError> {
+			Self::do_burn(who, amount)?;
+			Ok(amount)
+		}
+	}
+
+	impl<T: Config> Transfer<T::AccountId> for Pallet<T> {
+		fn transfer(
+			_asset: (),
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: T::Balance,
+			_keep_alive: bool,
+		) -> Result<T::Balance, DispatchError> {
+			Self::do_transfer(source, dest, amount)?;
+			Ok(amount)
+		}
+	}
+}
@@ -0,0 +1,169 @@
+// Tests to be written here
+
+use crate::{Error, Event as Erc20Event, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use frame_support::{parameter_types, traits::tokens::{
+	fungible::{self, ItemOf},
+	fungibles::{Inspect, Mutate, Transfer},
+	DepositConsequence, WithdrawConsequence,
+}};
+use sp_runtime::traits::BadOrigin;
+
+fn last_event() -> Erc20Event<Test> {
+	System::events().into_iter().filter_map(|r| match r.event {
+		Event::Erc20(inner) => Some(inner),
+		_ => None,
+	}).last().unwrap()
+}
+
+#[test]
+fn genesis_balances_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Erc20::total_supply(), 120);
+		assert_eq!(Erc20::balance_of(1), 100);
+		assert_eq!(Erc20::balance_of(2), 20);
+		assert_eq!(Erc20::balance_of(3), 0);
+	});
+}
+
+#[test]
+fn transfer_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Erc20::transfer(Origin::signed(1), 3, 10));
+		assert_eq!(Erc20::balance_of(1), 90);
+		assert_eq!(Erc20::balance_of(3), 10);
+		assert_eq!(Erc20::total_supply(), 120);
+		assert_eq!(last_event(), Erc20Event::Transfer(Some(1), Some(3), 10));
+	});
+}
+
+#[test]
+fn transfer_failed_when_balance_is_insufficient() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Erc20::transfer(Origin::root(), 3, 10), BadOrigin);
+		assert_noop!(Erc20::transfer(Origin::signed(2), 3, 21), Error::<Test>::InsufficientBalance);
+		assert_noop!(Erc20::transfer(Origin::signed(3), 1, 1), Error::<Test>::InsufficientBalance);
+	});
+}
+
+#[test]
+fn transfer_to_self_keeps_balance() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Erc20::transfer(Origin::signed(2), 2, 20));
+		assert_eq!(Erc20::balance_of(2), 20);
+		assert_noop!(Erc20::transfer(Origin::signed(2), 2, 21), Error::<Test>::InsufficientBalance);
+	});
+}
+
+#[test]
+fn approve_should_replace_allowance() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Erc20::approve(Origin::signed(1), 2, 30));
+		assert_eq!(Erc20::allowance(1, 2), 30);
+		assert_eq!(last_event(), Erc20Event::Approval(1, 2, 30));
+
+		assert_ok!(Erc20::approve(Origin::signed(1), 2, 5));
+		assert_eq!(Erc20::allowance(1, 2), 5);
+		assert_eq!(Erc20::allowance(2, 1), 0);
+	});
+}
+
+#[test]
+fn transfer_from_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Erc20::approve(Origin::signed(1), 2, 30));
+		assert_ok!(Erc20::transfer_from(Origin::signed(2), 1, 3, 10));
+
+		assert_eq!(Erc20::allowance(1, 2), 20);
+		assert_eq!(Erc20::balance_of(1), 90);
+		assert_eq!(Erc20::balance_of(3), 10);
+		assert_eq!(last_event(), Erc20Event::Transfer(Some(1), Some(3), 10));
+	});
+}
+
+#[test]
+fn transfer_from_failed_when_approval_or_balance_is_insufficient() {
+	new_test_ext().execute_with(|| {
+		// no allowance at all
+		assert_noop!(Erc20::transfer_from(Origin::signed(2), 1, 3, 10), Error::<Test>::InsufficientApproval);
+
+		assert_ok!(Erc20::approve(Origin::signed(1), 2, 30));
+		assert_noop!(Erc20::transfer_from(Origin::signed(2), 1, 3, 31), Error::<Test>::InsufficientApproval);
+
+		// the allowance exceeds the balance of the owner, nothing is spent
+		assert_ok!(Erc20::approve(Origin::signed(1), 2, 200));
+		assert_noop!(Erc20::transfer_from(Origin::signed(2), 1, 3, 101), Error::<Test>::InsufficientBalance);
+		assert_eq!(Erc20::allowance(1, 2), 200);
+	});
+}
+
+#[test]
+fn fungibles_inspect_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(<Erc20 as Inspect<u64>>::total_issuance(()), 120);
+		assert_eq!(<Erc20 as Inspect<u64>>::minimum_balance(()), 0);
+		assert_eq!(<Erc20 as Inspect<u64>>::balance((), &2), 20);
+		assert_eq!(<Erc20 as Inspect<u64>>::reducible_balance((), &2, true), 20);
+
+		assert_eq!(<Erc20 as Inspect<u64>>::can_deposit((), &3, 10), DepositConsequence::Success);
+		assert_eq!(<Erc20 as Inspect<u64>>::can_deposit((), &3, u64::MAX), DepositConsequence::Overflow);
+		assert_eq!(<Erc20 as Inspect<u64>>::can_withdraw((), &2, 20), WithdrawConsequence::Success);
+		assert_eq!(<Erc20 as Inspect<u64>>::can_withdraw((), &2, 21), WithdrawConsequence::NoFunds);
+	});
+}
+
+#[test]
+fn fungibles_mutate_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(<Erc20 as Mutate<u64>>::mint_into((), &3, 50));
+		assert_eq!(Erc20::balance_of(3), 50);
+		assert_eq!(Erc20::total_supply(), 170);
+		assert_eq!(last_event(), Erc20Event::Transfer(None, Some(3), 50));
+
+		assert_eq!(<Erc20 as Mutate<u64>>::burn_from((), &3, 20), Ok(20));
+		assert_eq!(Erc20::balance_of(3), 30);
+		assert_eq!(Erc20::total_supply(), 150);
+		assert_eq!(last_event(), Erc20Event::Transfer(Some(3), None, 20));
+
+		assert_noop!(<Erc20 as Mutate<u64>>::burn_from((), &3, 31), Error::<Test>::InsufficientBalance);
+		assert_noop!(<Erc20 as Mutate<u64>>::mint_into((), &3, u64::MAX), Error::<Test>::Overflow);
+	});
+}
+
+#[test]
+fn fungibles_transfer_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(<Erc20 as Transfer<u64>>::transfer((), &1, &3, 40, true), Ok(40));
+		assert_eq!(Erc20::balance_of(1), 60);
+		assert_eq!(Erc20::balance_of(3), 40);
+		assert_noop!(<Erc20 as Transfer<u64>>::transfer((), &2, &3, 21, false), Error::<Test>::InsufficientBalance);
+
+		// there is no existential deposit, keep alive or not the whole balance can be moved
+		assert_eq!(<Erc20 as Inspect<u64>>::can_withdraw((), &2, 20), WithdrawConsequence::Success);
+		assert_eq!(<Erc20 as Transfer<u64>>::transfer((), &2, &3, 20, true), Ok(20));
+		assert_eq!(Erc20::balance_of(2), 0);
+		// and the emptied account is removed
+		assert!(!crate::Balances::<Test>::contains_key(2));
+	});
+}
+
+parameter_types! {
+	pub const Erc20Asset: () = ();
+}
+
+// the asset as a single `fungible` currency, as pallets taking one see it
+type Erc20Item = ItemOf<Erc20, Erc20Asset, u64>;
+
+#[test]
+fn item_of_should_use_the_fungibles_impl() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(<Erc20Item as fungible::Inspect<u64>>::balance(&2), 20);
+
+		assert_ok!(<Erc20Item as fungible::Mutate<u64>>::mint_into(&3, 50));
+		assert_eq!(<Erc20Item as fungible::Transfer<u64>>::transfer(&3, &2, 30, true), Ok(30));
+		assert_eq!(Erc20::balance_of(2), 50);
+		assert_eq!(Erc20::balance_of(3), 20);
+		assert_eq!(<Erc20Item as fungible::Inspect<u64>>::total_issuance(), 170);
+		assert_eq!(last_event(), Erc20Event::Transfer(Some(3), Some(2), 30));
+	});
+}
@@ -0,0 +1,526 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// A FRAME pallet whose offchain worker fetches the BTC price and submits it on-chain.
+///
+/// How the price is submitted is chosen by the runtime with `Config::SubmissionMode`: in a
+/// signed transaction, in an unsigned transaction, or in an unsigned transaction carrying a
+/// payload signed by the offchain worker key.
+
+pub use pallet::*;
+
+use sp_core::crypto::KeyTypeId;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// Defines application identifier for crypto keys of this module.
+///
+/// Every module that deals with signatures needs to declare its unique identifier for
+/// its crypto keys.
+/// When offchain worker is signing transactions it's going to request keys of type
+/// `KeyTypeId` from the keystore and use the ones it finds to sign the transaction.
+/// The keys can be inserted manually via RPC (see `author_insertKey`).
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"btc!");
+
+/// Based on the above `KeyTypeId` we need to generate a pallet-specific crypto type wrappers.
+/// We can use from supported crypto kinds (`sr25519`, `ed25519` and `ecdsa`) and augment
+/// the types with this pallet-specific identifier.
+pub mod crypto {
+	use super::KEY_TYPE;
+	use sp_core::sr25519::Signature as Sr25519Signature;
+	use sp_runtime::{
+		app_crypto::{app_crypto, sr25519},
+		traits::Verify,
+		MultiSignature, MultiSigner,
+	};
+
+	app_crypto!(sr25519, KEY_TYPE);
+
+	pub struct AuthorityId;
+
+	impl frame_system::offchain::AppCrypto<MultiSigner, MultiSignature> for AuthorityId {
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+
+	// implemented for the mock runtime in tests
+	impl frame_system::offchain::AppCrypto<<Sr25519Signature as Verify>::Signer, Sr25519Signature>
+		for AuthorityId
+	{
+		type RuntimeAppPublic = Public;
+		type GenericSignature = sp_core::sr25519::Signature;
+		type GenericPublic = sp_core::sr25519::Public;
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use codec::{Decode, Encode};
+	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+	use frame_system::{
+		offchain::{
+			AppCrypto, CreateSignedTransaction, SendSignedTransaction, SendUnsignedTransaction,
+			SignedPayload, Signer, SigningTypes, SubmitTransaction,
+		},
+		pallet_prelude::*,
+	};
+	use ocw_price_parser::ParseError;
+	use scale_info::TypeInfo;
+	use sp_runtime::{
+		offchain::{http, storage::StorageValueRef, Duration},
+		traits::{CheckedDiv, IdentifyAccount, SaturatedConversion, Saturating, Zero},
+		transaction_validity::{InvalidTransaction, TransactionPriority, ValidTransaction},
+		FixedPointNumber, FixedU128, RuntimeDebug,
+	};
+	use sp_std::prelude::*;
+
+	const PRICE_URL: &str = "https://min-api.cryptocompare.com/data/price?fsym=BTC&tsyms=USD";
+	const FETCH_TIMEOUT_PERIOD: u64 = 3000; // in milli-seconds
+
+	/// Key of the `FetchState` in the offchain local storage.
+	pub const FETCH_STATE_KEY: &[u8] = b"ocw-price::fetch-state";
+
+	/// How the offchain worker submits the prices.
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub enum SubmissionMode {
+		/// In a signed transaction, paying its fee.
+		Signed,
+		/// In an unsigned transaction, which anyone can send.
+		Unsigned,
+		/// In an unsigned transaction with a payload signed by the offchain worker key.
+		UnsignedWithSignedPayload,
+	}
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+	pub struct PricePayload<Public, BlockNumber> {
+		pub price: FixedU128,
+		/// The block the price is fetched in, the payload cannot be replayed later on.
+		pub block_number: BlockNumber,
+		pub public: Public,
+	}
+
+	impl<T: SigningTypes> SignedPayload<T> for PricePayload<T::Public, T::BlockNumber> {
+		fn public(&self) -> T::Public {
+			self.public.clone()
+		}
+	}
+
+	/// Reasons fetching the price fails.
+	#[derive(RuntimeDebug, PartialEq, Eq)]
+	pub enum FetchError {
+		/// The request could not be sent or no response came before the deadline.
+		Transport(http::Error),
+		/// The endpoint answered with another status than 200.
+		Status(u16),
+		/// The body is not UTF-8 encoded JSON.
+		Encoding,
+		/// The JSON has no `USD` field holding a string or a number.
+		Schema,
+		/// The `USD` field holds a price that cannot be parsed.
+		InvalidPrice(ParseError),
+	}
+
+	impl From<http::Error> for FetchError {
+		fn from(error: http::Error) -> Self {
+			FetchError::Transport(error)
+		}
+	}
+
+	/// Failed fetches in a row, kept in the offchain local storage of the node so that the
+	/// offchain workers of the next blocks back off the failing endpoint.
+	#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug)]
+	pub struct FetchState<BlockNumber> {
+		pub failures: u32,
+		/// The block from which the endpoint is requested again.
+		pub retry_at: BlockNumber,
+	}
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + CreateSignedTransaction<Call<Self>> {
+		/// The identifier type for an offchain worker.
+		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The overarching dispatch call type.
+		type Call: From<Call<Self>>;
+
+		/// How the offchain worker submits the prices, transactions of the other modes are
+		/// rejected.
+		#[pallet::constant]
+		type SubmissionMode: Get<SubmissionMode>;
+
+		/// Number of recent prices kept on-chain.
+		#[pallet::constant]
+		type MaxLen: Get<u32>;
+
+		/// Number of blocks to wait between two unsigned prices.
+		#[pallet::constant]
+		type UnsignedInterval: Get<Self::BlockNumber>;
+
+		/// Base priority of the unsigned transactions, increased by the number of blocks the
+		/// price is overdue.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// Number of failed fetches in a row which opens the circuit breaker. Before that the
+		/// endpoint is retried after 1, 2, 4, ... blocks.
+		#[pallet::constant]
+		type MaxFetchFailures: Get<u32>;
+
+		/// Number of blocks the endpoint is not requested once the circuit breaker is open,
+		/// after which a single request is let through to close it again.
+		#[pallet::constant]
+		type BreakerCooldown: Get<Self::BlockNumber>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Ring buffer of the recently submitted prices, the oldest one is at `PricesHead` once
+	/// `MaxLen` prices are kept.
+	#[pallet::storage]
+	#[pallet::getter(fn prices)]
+	pub type Prices<T> = StorageValue<_, Vec<FixedU128>, ValueQuery>;
+
+	/// Index of the oldest price, which the next price replaces.
+	#[pallet::storage]
+	pub type PricesHead<T> = StorageValue<_, u32, ValueQuery>;
+
+	/// The block from which the next unsigned price is accepted.
+	#[pallet::storage]
+	#[pallet::getter(fn next_unsigned_at)]
+	pub type NextUnsignedAt<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A price is submitted, by an account unless it comes in an unsigned transaction.
+		/// \[price, who\]
+		NewPrice(FixedU128, Option<T::AccountId>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The transaction does not match the configured `SubmissionMode`.
+		WrongSubmissionMode,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn offchain_worker(block_number: T::BlockNumber) {
+			log::info!("ocw-price: offchain worker starts running");
+
+			if let Err(e) = Self::fetch_price_and_submit(block_number) {
+				log::error!("ocw-price: {}", e);
+			}
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Submit a price in a signed transaction.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn submit_price(origin: OriginFor<T>, price: FixedU128) -> DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				T::SubmissionMode::get() == SubmissionMode::Signed,
+				Error::<T>::WrongSubmissionMode
+			);
+
+			Self::add_price(Some(who), price);
+
+			Ok(().into())
+		}
+
+		/// Submit a price in an unsigned transaction, see `validate_unsigned`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn submit_price_unsigned(
+			origin: OriginFor<T>,
+			_block_number: T::BlockNumber,
+			price: FixedU128,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			Self::add_price(None, price);
+			Self::bump_next_unsigned_at();
+
+			Ok(().into())
+		}
+
+		/// Submit a price in an unsigned transaction with a signed payload, the signature is
+		/// checked in `validate_unsigned`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn submit_price_unsigned_with_signed_payload(
+			origin: OriginFor<T>,
+			price_payload: PricePayload<T::Public, T::BlockNumber>,
+			_signature: T::Signature,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			Self::add_price(Some(price_payload.public.into_account()), price_payload.price);
+			Self::bump_next_unsigned_at();
+
+			Ok(().into())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			let mode = T::SubmissionMode::get();
+			match call {
+				Call::submit_price_unsigned { block_number, .. }
+					if mode == SubmissionMode::Unsigned =>
+					Self::validate_price(block_number, &b"submit_price_unsigned"[..]),
+				Call::submit_price_unsigned_with_signed_payload { price_payload, signature }
+					if mode == SubmissionMode::UnsignedWithSignedPayload =>
+				{
+					if !SignedPayload::<T>::verify::<T::AuthorityId>(
+						price_payload,
+						signature.clone(),
+					) {
+						return InvalidTransaction::BadProof.into()
+					}
+					Self::validate_price(&price_payload.block_number, price_payload.public.encode())
+				},
+				_ => InvalidTransaction::Call.into(),
+			}
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Write `price` into the ring buffer, replacing the oldest price once it is full.
+		fn add_price(who: Option<T::AccountId>, price: FixedU128) {
+			log::info!("ocw-price: adding to the prices: {:?}", price);
+
+			let max_len = T::MaxLen::get() as usize;
+			Prices::<T>::mutate(|prices| {
+				if prices.len() < max_len {
+					prices.push(price);
+				} else if !prices.is_empty() {
+					// the buffer keeps its length when `MaxLen` is lowered
+					let head = PricesHead::<T>::get() as usize % prices.len();
+					prices[head] = price;
+					PricesHead::<T>::put(((head + 1) % prices.len()) as u32);
+				}
+			});
+
+			Self::deposit_event(Event::NewPrice(price, who));
+		}
+
+		/// The kept prices from the oldest to the latest.
+		pub fn latest_prices() -> Vec<FixedU128> {
+			let mut prices = Self::prices();
+			if !prices.is_empty() {
+				let head = PricesHead::<T>::get() as usize % prices.len();
+				prices.rotate_left(head);
+			}
+			prices
+		}
+
+		/// The average of the kept prices.
+		pub fn average_price() -> Option<FixedU128> {
+			let prices = Self::prices();
+			let sum =
+				prices.iter().fold(FixedU128::zero(), |sum, price| sum.saturating_add(*price));
+			sum.checked_div(&FixedU128::saturating_from_integer(prices.len() as u64))
+		}
+
+		fn bump_next_unsigned_at() {
+			let now = <frame_system::Pallet<T>>::block_number();
+			NextUnsignedAt::<T>::put(now.saturating_add(T::UnsignedInterval::get()));
+		}
+
+		/// Only one unsigned price is accepted every `UnsignedInterval` blocks, and prices
+		/// fetched more than an interval ago cannot be replayed.
+		fn validate_price(
+			block_number: &T::BlockNumber,
+			provides: impl Encode,
+		) -> TransactionValidity {
+			let now = <frame_system::Pallet<T>>::block_number();
+			if *block_number > now {
+				return InvalidTransaction::Future.into()
+			}
+			let next_unsigned_at = Self::next_unsigned_at();
+			if next_unsigned_at > now ||
+				block_number.saturating_add(T::UnsignedInterval::get()) < now
+			{
+				return InvalidTransaction::Stale.into()
+			}
+
+			let overdue: TransactionPriority =
+				now.saturating_sub(next_unsigned_at).saturated_into();
+			ValidTransaction::with_tag_prefix("OcwPrice")
+				.priority(T::UnsignedPriority::get().saturating_add(overdue))
+				// a single price of each sender can be in the pool for the same interval
+				.and_provides((next_unsigned_at, provides.encode()))
+				.longevity(T::UnsignedInterval::get().saturated_into::<u64>())
+				.propagate(true)
+				.build()
+		}
+
+		fn fetch_price_and_submit(block_number: T::BlockNumber) -> Result<(), &'static str> {
+			let mode = T::SubmissionMode::get();
+			// The transaction would be rejected by `validate_unsigned` anyway.
+			if mode != SubmissionMode::Signed && Self::next_unsigned_at() > block_number {
+				return Err("too early to send unsigned transaction")
+			}
+
+			let price = Self::fetch_price_with_backoff(block_number)?;
+
+			match mode {
+				SubmissionMode::Signed => Self::send_signed(price),
+				SubmissionMode::Unsigned => {
+					let call = Call::submit_price_unsigned { block_number, price };
+					SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
+						.map_err(|()| "unable to submit unsigned transaction")
+				},
+				SubmissionMode::UnsignedWithSignedPayload => {
+					let (_, result) = Signer::<T, T::AuthorityId>::any_account()
+						.send_unsigned_transaction(
+							|account| PricePayload {
+								price,
+								block_number,
+								public: account.public.clone(),
+							},
+							|price_payload, signature| {
+								Call::submit_price_unsigned_with_signed_payload {
+									price_payload,
+									signature,
+								}
+							},
+						)
+						.ok_or("no local accounts available")?;
+					result.map_err(|()| "unable to submit unsigned transaction with signed payload")
+				},
+			}
+		}
+
+		fn send_signed(price: FixedU128) -> Result<(), &'static str> {
+			let signer = Signer::<T, T::AuthorityId>::all_accounts();
+			if !signer.can_sign() {
+				return Err(
+					"no local accounts available, consider adding one via `author_insertKey` RPC",
+				)
+			}
+
+			// Submit signed will return a vector of results for all accounts that were found in the
+			// local keystore with expected `KEY_TYPE`.
+			let results = signer.send_signed_transaction(|_account| Call::submit_price { price });
+			for (account, result) in &results {
+				match result {
+					Ok(()) =>
+						log::info!("ocw-price: [{:?}] submitted price of {:?}", account.id, price),
+					Err(e) => log::error!(
+						"ocw-price: [{:?}] failed to submit transaction: {:?}",
+						account.id,
+						e
+					),
+				}
+			}
+
+			Ok(())
+		}
+
+		/// Fetch the price unless the endpoint is backed off, and record the outcome in the
+		/// `FetchState`.
+		fn fetch_price_with_backoff(
+			block_number: T::BlockNumber,
+		) -> Result<FixedU128, &'static str> {
+			let state_ref = StorageValueRef::persistent(FETCH_STATE_KEY);
+			let state =
+				state_ref.get::<FetchState<T::BlockNumber>>().ok().flatten().unwrap_or_default();
+			if state.retry_at > block_number {
+				return Err(if state.failures >= T::MaxFetchFailures::get() {
+					"circuit breaker of the price endpoint is open"
+				} else {
+					"backing off the price endpoint"
+				})
+			}
+
+			// Make an external HTTP request to fetch the current price.
+			// Note this call will block until response is received.
+			match Self::fetch_price() {
+				Ok(price) => {
+					if state.failures > 0 {
+						state_ref.set(&FetchState::<T::BlockNumber>::default());
+					}
+					Ok(price)
+				},
+				Err(e) => {
+					log::warn!("ocw-price: failed to fetch price: {:?}", e);
+					// The offchain workers of several blocks may fail at the same time.
+					let _ = state_ref.mutate(|state| {
+						let mut state: FetchState<T::BlockNumber> =
+							state.ok().flatten().unwrap_or_default();
+						state.failures = state.failures.saturating_add(1);
+						state.retry_at = block_number.saturating_add(Self::backoff(state.failures));
+						Ok::<_, ()>(state)
+					});
+					Err("failed to fetch price")
+				},
+			}
+		}
+
+		/// Number of blocks to wait after `failures` failed fetches in a row, doubling from a
+		/// single block until the circuit breaker opens for `BreakerCooldown` blocks.
+		fn backoff(failures: u32) -> T::BlockNumber {
+			let cooldown = T::BreakerCooldown::get();
+			if failures >= T::MaxFetchFailures::get() {
+				return cooldown
+			}
+			let delay: T::BlockNumber = 2u32.saturating_pow(failures.saturating_sub(1)).into();
+			delay.min(cooldown)
+		}
+
+		fn fetch_price() -> Result<FixedU128, FetchError> {
+			let deadline =
+				sp_io::offchain::timestamp().add(Duration::from_millis(FETCH_TIMEOUT_PERIOD));
+			// Initiate an external HTTP GET request.
+			// This is using high-level wrappers from `sp_runtime`, for the low-level calls that
+			// you can find in `sp_io`.
+			let pending = http::Request::get(PRICE_URL)
+				.deadline(deadline)
+				.send()
+				.map_err(|_| FetchError::Transport(http::Error::IoError))?;
+			// The request is driven by the host, we wait for it until the deadline.
+			let response = pending
+				.try_wait(deadline)
+				.map_err(|_| FetchError::Transport(http::Error::DeadlineReached))??;
+			if response.code != 200 {
+				return Err(FetchError::Status(response.code))
+			}
+
+			let body = response.body().collect::<Vec<u8>>();
+			let price = Self::parse_price(&body)?;
+			log::info!("ocw-price: got price: {:?}", price);
+			Ok(price)
+		}
+
+		/// Parse the `USD` field of the response, either a JSON number or a JSON string.
+		pub(crate) fn parse_price(body: &[u8]) -> Result<FixedU128, FetchError> {
+			let body = sp_std::str::from_utf8(body).map_err(|_| FetchError::Encoding)?;
+			let document: serde_json::Value =
+				serde_json::from_str(body).map_err(|_| FetchError::Encoding)?;
+			let parsed = match document.get("USD") {
+				Some(serde_json::Value::String(price)) => ocw_price_parser::parse(price),
+				Some(serde_json::Value::Number(price)) => match price.as_u64() {
+					Some(price) => ocw_price_parser::parse_number(&price),
+					// fractional and negative numbers go through `f64`
+					None =>
+						ocw_price_parser::parse_number(&price.as_f64().ok_or(FetchError::Schema)?),
+				},
+				_ => return Err(FetchError::Schema),
+			};
+			parsed.map(FixedU128::from_inner).map_err(FetchError::InvalidPrice)
+		}
+	}
+}
@@ -0,0 +1,136 @@
+// Creating mock runtime here
+
+use crate as pallet_ocw_price;
+use crate::SubmissionMode;
+use frame_support::{parameter_types, traits::Get};
+use frame_system as system;
+use sp_core::{sr25519::Signature, H256};
+use sp_runtime::{
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+};
+use std::cell::RefCell;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		OcwPrice: pallet_ocw_price::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = sp_core::sr25519::Public;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+pub type Extrinsic = TestXt<Call, ()>;
+type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+impl frame_system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: frame_system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+thread_local! {
+	static SUBMISSION_MODE: RefCell<SubmissionMode> = RefCell::new(SubmissionMode::Signed);
+}
+
+/// The submission mode, which tests switch with `set_submission_mode`.
+pub struct Mode;
+
+impl Get<SubmissionMode> for Mode {
+	fn get() -> SubmissionMode {
+		SUBMISSION_MODE.with(|mode| *mode.borrow())
+	}
+}
+
+pub fn set_submission_mode(mode: SubmissionMode) {
+	SUBMISSION_MODE.with(|current| *current.borrow_mut() = mode);
+}
+
+parameter_types! {
+	pub const MaxLen: u32 = 3;
+	pub const UnsignedInterval: u64 = 3;
+	pub const UnsignedPriority: u64 = 100;
+	pub const MaxFetchFailures: u32 = 3;
+	pub const BreakerCooldown: u64 = 10;
+}
+
+impl pallet_ocw_price::Config for Test {
+	type AuthorityId = pallet_ocw_price::crypto::AuthorityId;
+	type Event = Event;
+	type Call = Call;
+	type SubmissionMode = Mode;
+	type MaxLen = MaxLen;
+	type UnsignedInterval = UnsignedInterval;
+	type UnsignedPriority = UnsignedPriority;
+	type MaxFetchFailures = MaxFetchFailures;
+	type BreakerCooldown = BreakerCooldown;
+}
+
+// This function basically just builds a genesis storage key/value store according to
+// our desired mockup.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	// events are not recorded in the genesis block
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
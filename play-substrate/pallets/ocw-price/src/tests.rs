@@ -0,0 +1,355 @@
+// Tests to be written here
+
+use crate::{
+	mock::*, Error, Event as OcwPriceEvent, FetchError, FetchState, PricePayload, SubmissionMode,
+	FETCH_STATE_KEY,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok, dispatch::Dispatchable, traits::Hooks, unsigned::ValidateUnsigned,
+};
+use ocw_price_parser::ParseError;
+use parking_lot::RwLock;
+use sp_core::{
+	offchain::{
+		testing::{self, OffchainState, PoolState},
+		OffchainDbExt, OffchainWorkerExt, TransactionPoolExt,
+	},
+	sr25519::Public,
+};
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+use sp_runtime::{
+	offchain::storage::StorageValueRef,
+	transaction_validity::{InvalidTransaction, TransactionSource, TransactionValidity},
+	FixedPointNumber, FixedU128, RuntimeAppPublic,
+};
+use std::sync::Arc;
+
+const PHRASE: &str = "news slush supreme milk chapter athlete soap sausage put clutch what kitten";
+const PRICE_URL: &str = "https://min-api.cryptocompare.com/data/price?fsym=BTC&tsyms=USD";
+
+fn offchain_ext(
+	mode: SubmissionMode,
+) -> (sp_io::TestExternalities, Arc<RwLock<OffchainState>>, Arc<RwLock<PoolState>>, Public) {
+	set_submission_mode(mode);
+
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	let local = SyncCryptoStore::sr25519_generate_new(
+		&keystore,
+		crate::crypto::Public::ID,
+		Some(&format!("{}/hunter1", PHRASE)),
+	)
+	.unwrap();
+
+	let mut t = new_test_ext();
+	t.register_extension(OffchainDbExt::new(offchain.clone()));
+	t.register_extension(OffchainWorkerExt::new(offchain));
+	t.register_extension(TransactionPoolExt::new(pool));
+	t.register_extension(KeystoreExt(Arc::new(keystore)));
+	(t, offchain_state, pool_state, local)
+}
+
+fn expect_price(state: &Arc<RwLock<OffchainState>>, body: &[u8]) {
+	state.write().expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: PRICE_URL.into(),
+		response: Some(body.to_vec()),
+		sent: true,
+		..Default::default()
+	});
+}
+
+// run the offchain worker on top of block `n` and return the transaction it sends
+fn run_worker(n: u64, pool_state: &Arc<RwLock<PoolState>>) -> Option<Extrinsic> {
+	System::set_block_number(n);
+	OcwPrice::offchain_worker(n);
+	let tx = pool_state.write().transactions.pop()?;
+	assert!(pool_state.read().transactions.is_empty());
+	Some(Extrinsic::decode(&mut &tx[..]).unwrap())
+}
+
+fn validate(call: &crate::Call<Test>) -> TransactionValidity {
+	OcwPrice::validate_unsigned(TransactionSource::External, call)
+}
+
+fn inner_call(tx: &Extrinsic) -> crate::Call<Test> {
+	match tx.call {
+		Call::OcwPrice(ref inner) => inner.clone(),
+		_ => panic!("unexpected call"),
+	}
+}
+
+fn last_event() -> OcwPriceEvent<Test> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| match r.event {
+			Event::OcwPrice(inner) => Some(inner),
+			_ => None,
+		})
+		.last()
+		.unwrap()
+}
+
+fn fetch_state() -> Option<FetchState<u64>> {
+	StorageValueRef::persistent(FETCH_STATE_KEY).get().unwrap()
+}
+
+fn requests(state: &Arc<RwLock<OffchainState>>) -> usize {
+	state.read().requests.len()
+}
+
+fn price(text: &str) -> FixedU128 {
+	FixedU128::from_inner(ocw_price_parser::parse(text).unwrap())
+}
+
+#[test]
+fn parse_price_keeps_all_decimals() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(OcwPrice::parse_price(br#"{"USD":10234.56}"#).ok(), Some(price("10234.56")));
+		assert_eq!(OcwPrice::parse_price(br#"{"USD":"0.000123"}"#).ok(), Some(price("0.000123")));
+		assert_eq!(
+			OcwPrice::parse_price(br#"{"USD":7}"#).ok(),
+			Some(FixedU128::saturating_from_integer(7))
+		);
+	});
+}
+
+#[test]
+fn parse_price_classifies_bad_payloads() {
+	new_test_ext().execute_with(|| {
+		let parse = |body: &[u8]| OcwPrice::parse_price(body).unwrap_err();
+
+		assert_eq!(parse(b"\xff\xfe{}"), FetchError::Encoding);
+		assert_eq!(parse(b"<html>"), FetchError::Encoding);
+		assert_eq!(parse(br#"{"USD":1"#), FetchError::Encoding);
+		assert_eq!(parse(br#"{"EUR":1}"#), FetchError::Schema);
+		assert_eq!(parse(br#"{"USD":null}"#), FetchError::Schema);
+		assert_eq!(parse(br#"[10234.56]"#), FetchError::Schema);
+		assert_eq!(parse(br#"{"USD":-7}"#), FetchError::InvalidPrice(ParseError::Negative));
+		assert_eq!(
+			parse(br#"{"USD":"n/a"}"#),
+			FetchError::InvalidPrice(ParseError::InvalidCharacter)
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_submits_signed_price() {
+	let (mut t, state, pool_state, local) = offchain_ext(SubmissionMode::Signed);
+	t.execute_with(|| {
+		expect_price(&state, br#"{"USD":10234.56}"#);
+		let tx = run_worker(1, &pool_state).unwrap();
+
+		assert_eq!(tx.signature, Some((0, ())));
+		assert_eq!(inner_call(&tx), crate::Call::submit_price { price: price("10234.56") });
+		assert_ok!(tx.call.dispatch(Origin::signed(local)));
+		assert_eq!(OcwPrice::prices(), vec![price("10234.56")]);
+		assert_eq!(last_event(), OcwPriceEvent::NewPrice(price("10234.56"), Some(local)));
+	});
+}
+
+#[test]
+fn offchain_worker_submits_unsigned_price() {
+	let (mut t, state, pool_state, _) = offchain_ext(SubmissionMode::Unsigned);
+	t.execute_with(|| {
+		expect_price(&state, br#"{"USD":"10234.56"}"#);
+		let tx = run_worker(1, &pool_state).unwrap();
+
+		assert_eq!(tx.signature, None);
+		let call = inner_call(&tx);
+		assert_eq!(
+			call,
+			crate::Call::submit_price_unsigned { block_number: 1, price: price("10234.56") }
+		);
+		assert_ok!(validate(&call));
+		assert_ok!(tx.call.dispatch(Origin::none()));
+		assert_eq!(OcwPrice::prices(), vec![price("10234.56")]);
+		assert_eq!(last_event(), OcwPriceEvent::NewPrice(price("10234.56"), None));
+	});
+}
+
+#[test]
+fn offchain_worker_submits_unsigned_price_with_signed_payload() {
+	let (mut t, state, pool_state, local) = offchain_ext(SubmissionMode::UnsignedWithSignedPayload);
+	t.execute_with(|| {
+		expect_price(&state, br#"{"USD":10234.56}"#);
+		let tx = run_worker(1, &pool_state).unwrap();
+
+		assert_eq!(tx.signature, None);
+		let call = inner_call(&tx);
+		if let crate::Call::submit_price_unsigned_with_signed_payload {
+			ref price_payload, ..
+		} = call
+		{
+			assert_eq!(
+				price_payload,
+				&PricePayload { price: price("10234.56"), block_number: 1, public: local }
+			);
+		} else {
+			panic!("unexpected call");
+		}
+		assert_ok!(validate(&call));
+		assert_ok!(tx.call.dispatch(Origin::none()));
+		assert_eq!(last_event(), OcwPriceEvent::NewPrice(price("10234.56"), Some(local)));
+	});
+}
+
+#[test]
+fn tampered_signed_payload_is_rejected() {
+	let (mut t, state, pool_state, _) = offchain_ext(SubmissionMode::UnsignedWithSignedPayload);
+	t.execute_with(|| {
+		expect_price(&state, br#"{"USD":10234.56}"#);
+		let tx = run_worker(1, &pool_state).unwrap();
+
+		let call = match inner_call(&tx) {
+			crate::Call::submit_price_unsigned_with_signed_payload {
+				mut price_payload,
+				signature,
+			} => {
+				price_payload.price = price("1");
+				crate::Call::submit_price_unsigned_with_signed_payload { price_payload, signature }
+			},
+			_ => panic!("unexpected call"),
+		};
+		assert_eq!(validate(&call), InvalidTransaction::BadProof.into());
+	});
+}
+
+#[test]
+fn transactions_of_other_modes_are_rejected() {
+	let (mut t, _, _, local) = offchain_ext(SubmissionMode::Unsigned);
+	t.execute_with(|| {
+		assert_noop!(
+			OcwPrice::submit_price(Origin::signed(local), price("1")),
+			Error::<Test>::WrongSubmissionMode
+		);
+
+		set_submission_mode(SubmissionMode::Signed);
+		let call = crate::Call::submit_price_unsigned { block_number: 1, price: price("1") };
+		assert_eq!(validate(&call), InvalidTransaction::Call.into());
+	});
+}
+
+#[test]
+fn invalid_prices_are_not_submitted() {
+	let (mut t, state, pool_state, _) = offchain_ext(SubmissionMode::Unsigned);
+	t.execute_with(|| {
+		expect_price(&state, br#"{"USD":-10234.56}"#);
+		assert!(run_worker(1, &pool_state).is_none());
+
+		expect_price(&state, br#"{"Response":"Error"}"#);
+		assert!(run_worker(2, &pool_state).is_none());
+	});
+}
+
+#[test]
+fn unsigned_prices_are_rate_limited() {
+	let (mut t, state, pool_state, _) = offchain_ext(SubmissionMode::Unsigned);
+	t.execute_with(|| {
+		expect_price(&state, br#"{"USD":10234.56}"#);
+		let tx = run_worker(1, &pool_state).unwrap();
+		let call = inner_call(&tx);
+		assert_ok!(tx.call.dispatch(Origin::none()));
+		assert_eq!(OcwPrice::next_unsigned_at(), 4);
+
+		// replaying the transaction is rejected
+		assert_eq!(validate(&call), InvalidTransaction::Stale.into());
+		// the offchain worker waits for the next interval
+		assert!(run_worker(2, &pool_state).is_none());
+
+		System::set_block_number(4);
+		assert_ok!(validate(&call));
+		// but prices fetched more than an interval ago are stale
+		System::set_block_number(5);
+		assert_eq!(validate(&call), InvalidTransaction::Stale.into());
+		let future = crate::Call::submit_price_unsigned { block_number: 6, price: price("1") };
+		assert_eq!(validate(&future), InvalidTransaction::Future.into());
+
+		// overdue prices have a higher priority
+		System::set_block_number(7);
+		let overdue = crate::Call::submit_price_unsigned { block_number: 7, price: price("1") };
+		assert_eq!(validate(&overdue).unwrap().priority, 103);
+	});
+}
+
+#[test]
+fn prices_are_kept_in_a_ring_buffer() {
+	let (mut t, _, _, local) = offchain_ext(SubmissionMode::Signed);
+	t.execute_with(|| {
+		assert_eq!(OcwPrice::average_price(), None);
+
+		for n in 1..=5 {
+			assert_ok!(OcwPrice::submit_price(
+				Origin::signed(local),
+				FixedU128::saturating_from_integer(n)
+			));
+		}
+
+		// the oldest prices are replaced
+		let prices = |ns: &[u64]| {
+			ns.iter().map(|n| FixedU128::saturating_from_integer(*n)).collect::<Vec<_>>()
+		};
+		assert_eq!(OcwPrice::prices(), prices(&[4, 5, 3]));
+		assert_eq!(OcwPrice::latest_prices(), prices(&[3, 4, 5]));
+		assert_eq!(OcwPrice::average_price(), Some(FixedU128::saturating_from_integer(4)));
+	});
+}
+
+#[test]
+fn failed_fetches_back_off_exponentially() {
+	let (mut t, state, pool_state, _) = offchain_ext(SubmissionMode::Signed);
+	t.execute_with(|| {
+		expect_price(&state, b"<html>");
+		assert!(run_worker(1, &pool_state).is_none());
+		assert_eq!(fetch_state(), Some(FetchState { failures: 1, retry_at: 2 }));
+
+		expect_price(&state, br#"{"Response":"Error"}"#);
+		assert!(run_worker(2, &pool_state).is_none());
+		assert_eq!(fetch_state(), Some(FetchState { failures: 2, retry_at: 4 }));
+
+		// the endpoint is not requested while backing off
+		assert!(run_worker(3, &pool_state).is_none());
+		assert_eq!(requests(&state), 2);
+
+		// a successful fetch resets the backoff
+		expect_price(&state, br#"{"USD":10234.56}"#);
+		assert!(run_worker(4, &pool_state).is_some());
+		assert_eq!(fetch_state(), Some(FetchState::default()));
+
+		expect_price(&state, br#"{"USD":"n/a"}"#);
+		assert!(run_worker(5, &pool_state).is_none());
+		assert_eq!(fetch_state(), Some(FetchState { failures: 1, retry_at: 6 }));
+	});
+}
+
+#[test]
+fn circuit_breaker_opens_after_failures_in_a_row() {
+	let (mut t, state, pool_state, _) = offchain_ext(SubmissionMode::Signed);
+	t.execute_with(|| {
+		for (n, body) in [(1, &b"\xff"[..]), (2, b"{}"), (4, br#"{"USD":-1}"#)] {
+			expect_price(&state, body);
+			assert!(run_worker(n, &pool_state).is_none());
+		}
+		// open for `BreakerCooldown` blocks
+		assert_eq!(fetch_state(), Some(FetchState { failures: 3, retry_at: 14 }));
+		for n in 5..14 {
+			assert!(run_worker(n, &pool_state).is_none());
+		}
+		assert_eq!(requests(&state), 3);
+
+		// a single request is let through, which opens the breaker again on failure
+		expect_price(&state, b"");
+		assert!(run_worker(14, &pool_state).is_none());
+		assert_eq!(fetch_state(), Some(FetchState { failures: 4, retry_at: 24 }));
+		assert!(run_worker(23, &pool_state).is_none());
+		assert_eq!(requests(&state), 4);
+
+		// and closes it on success
+		expect_price(&state, br#"{"USD":10234.56}"#);
+		let tx = run_worker(24, &pool_state).unwrap();
+		assert_eq!(inner_call(&tx), crate::Call::submit_price { price: price("10234.56") });
+		assert_eq!(fetch_state(), Some(FetchState::default()));
+	});
+}
@@ -1,5 +0,0 @@
-// Tests to be written here
-
-use crate::{Error, mock::*};
-use frame_support::{assert_ok, assert_noop};
-
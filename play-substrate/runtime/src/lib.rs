@@ -31,7 +31,7 @@ pub use frame_support::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
 		IdentityFee, Weight,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
@@ -281,9 +281,35 @@ impl pallet_poe::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const CoinflipRevealDelay: BlockNumber = 3;
+	pub const CoinflipRevealPeriod: BlockNumber = 10 * MINUTES;
+	pub const CoinflipHouseEdge: Permill = Permill::from_percent(2);
+	pub const CoinflipPalletId: PalletId = PalletId(*b"py/cflip");
+	pub const CoinflipRoundDuration: BlockNumber = 10 * MINUTES;
+	pub const CoinflipMinBet: Balance = 1_000;
+	pub const CoinflipMaxPayoutsPerBlock: u32 = 50;
+	pub const CoinflipLimitPeriod: BlockNumber = DAYS;
+}
+
 impl pallet_coinflip::Config for Runtime {
 	type Event = Event;
 	type Randomness = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type PalletId = CoinflipPalletId;
+	type RevealDelay = CoinflipRevealDelay;
+	type RevealPeriod = CoinflipRevealPeriod;
+	type HouseEdge = CoinflipHouseEdge;
+	type RoundDuration = CoinflipRoundDuration;
+	type MinBet = CoinflipMinBet;
+	type MaxPayoutsPerBlock = CoinflipMaxPayoutsPerBlock;
+	type LimitPeriod = CoinflipLimitPeriod;
+	type WeightInfo = pallet_coinflip::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_erc20::Config for Runtime {
+	type Balance = Balance;
+	type Event = Event;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -305,6 +331,7 @@ construct_runtime!(
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
 		PoeModule: pallet_poe::{Pallet, Call, Storage, Event<T>},
 		CoinFlipModule: pallet_coinflip::{Pallet, Call, Storage, Event<T>},
+		Erc20: pallet_erc20::{Pallet, Call, Storage, Config<T>, Event<T>},
 	}
 );
 
@@ -466,6 +493,12 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_coinflip_runtime_api::CoinflipApi<Block, AccountId, Balance> for Runtime {
+		fn stats(who: AccountId) -> pallet_coinflip::GameStats<Balance> {
+			CoinFlipModule::stats(who)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -482,6 +515,7 @@ impl_runtime_apis! {
 			list_benchmark!(list, extra, pallet_balances, Balances);
 			list_benchmark!(list, extra, pallet_timestamp, Timestamp);
 			list_benchmark!(list, extra, pallet_template, TemplateModule);
+			list_benchmark!(list, extra, pallet_coinflip, CoinFlipModule);
 
 			let storage_info = AllPalletsWithSystem::storage_info();
 
@@ -516,6 +550,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
 			add_benchmark!(params, batches, pallet_template, TemplateModule);
+			add_benchmark!(params, batches, pallet_coinflip, CoinFlipModule);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
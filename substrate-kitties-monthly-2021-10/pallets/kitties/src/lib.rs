@@ -11,6 +11,8 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod migrations;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::{pallet_prelude::*};
@@ -21,7 +23,7 @@ pub mod pallet {
 		// let failure change be discarded
 		transactional
 	};
-	use frame_support::traits::ReservableCurrency;
+	use frame_support::traits::{ReservableCurrency, StorageVersion};
 	// for serialization
 	use codec::{Encode, Decode};
 	// for storage hashing
@@ -30,6 +32,12 @@ pub mod pallet {
 	use scale_info::TypeInfo;
 	#[cfg(feature = "std")]
 	use frame_support::serde::{Deserialize, Serialize};
+	// for usd prices
+	use frame_support::sp_runtime::{
+		traits::{CheckedDiv, SaturatedConversion, Saturating, Zero},
+		FixedPointNumber, FixedU128,
+	};
+	use core::convert::TryInto;
 	
 
 //============================================================
@@ -45,7 +53,7 @@ pub mod pallet {
 	pub struct Kitty<T: Config>{
 		// 16 bytes u8 vector(16 * u8)
 		pub dna: [u8; 16],
-		pub price: Option<BalanceOf<T>>,
+		pub price: Option<KittyPrice<BalanceOf<T>>>,
 		pub gender: Gender,
 		pub owner: AccountOf<T>
 	}
@@ -61,10 +69,37 @@ pub mod pallet {
 	}
 	// define index of kitty, u32
 	type KittyIndex = u64;
+
+	// Asking price of a kitty, in native currency or in USD.
+	// USD prices are converted to native currency with the oracle price when the kitty is bought.
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum KittyPrice<Balance> {
+		Native(Balance),
+		Usd(FixedU128),
+	}
+
+	// Source of the USD price of the native currency.
+	// e.g. with the ocw-example pallet in the runtime:
+	//   `OcwDemo::aggregated_price(b"DOT".to_vec()).map(|price| (price.price, price.block_number))`
+	pub trait PriceOracle<BlockNumber> {
+		// USD price of one native token, and the block it was updated in.
+		fn usd_price() -> Option<(FixedU128, BlockNumber)>;
+	}
+
+	// No oracle: USD listings are rejected.
+	impl<BlockNumber> PriceOracle<BlockNumber> for () {
+		fn usd_price() -> Option<(FixedU128, BlockNumber)> {
+			None
+		}
+	}
 //============================================================
 
+	// Version 1 lists kitties with a KittyPrice, see migrations::v1.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 //============================================================
@@ -83,6 +118,14 @@ pub mod pallet {
 		type MaxKittyOwned: Get<u32>;
 		#[pallet::constant]
 		type ReservationFee: Get<BalanceOf<Self>>;
+		// The USD price of the native currency, for USD listings
+		type PriceOracle: PriceOracle<Self::BlockNumber>;
+		// Oracle prices older than this many blocks are stale
+		#[pallet::constant]
+		type MaxPriceAge: Get<Self::BlockNumber>;
+		// Balance of one native token, which the oracle price is given for
+		#[pallet::constant]
+		type NativeUnit: Get<BalanceOf<Self>>;
 	}
 
 //============================================================
@@ -157,7 +200,7 @@ pub mod pallet {
 		// A new Kitty was sucessfully created. \[sender, kitty_id\]
 		KittyCreated(T::AccountId, KittyIndex, BalanceOf<T>),
 		// Kitty price was sucessfully set. \[sender, kitty_id, new_price\]
-		KittyPriceSet(T::AccountId, KittyIndex, Option<BalanceOf<T>>),
+		KittyPriceSet(T::AccountId, KittyIndex, Option<KittyPrice<BalanceOf<T>>>),
 		// A Kitty was sucessfully transferred. \[from, to, kitty_id\]
 		KittyTransferred(T::AccountId, T::AccountId, KittyIndex),
 		// A Kitty was sucessfully bought. \[buyer, seller, kitty_id, bid_price\]
@@ -189,7 +232,25 @@ pub mod pallet {
 		// Ensures that the buying price is greater than the asking price.
 		KittyBidPriceTooLow,
 		// Ensures that an account has enough funds to purchase a Kitty.
-		NotEnoughBalance
+		NotEnoughBalance,
+		// The oracle has no USD price of the native currency.
+		PriceUnavailable,
+		// The oracle price is older than MaxPriceAge.
+		StalePrice,
+		// The USD price cannot be converted to native currency.
+		PriceOverflow
+	}
+
+//============================================================
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() < 1 {
+				return crate::migrations::v1::migrate::<T>();
+			}
+			0
+		}
 	}
 
 //============================================================
@@ -214,11 +275,15 @@ pub mod pallet {
 		pub fn set_price(
 			origin: OriginFor<T>,
 			kitty_id: KittyIndex,
-			new_price: Option<BalanceOf<T>>
+			new_price: Option<KittyPrice<BalanceOf<T>>>
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 			ensure!(Self::is_kitty_owner(&kitty_id, &sender)?, <Error<T>>::NotKittyOwner);
 			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExist)?;
+			// USD listings need a fresh oracle price
+			if let Some(ref price) = new_price {
+				Self::native_price(price)?;
+			}
 			kitty.price = new_price.clone();
 			<Kitties<T>>::insert(&kitty_id, kitty);
 			Self::deposit_event(Event::KittyPriceSet(sender, kitty_id, new_price));
@@ -255,7 +320,8 @@ pub mod pallet {
 			let buyer = ensure_signed(origin)?;
 			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExist)?;
 			ensure!(kitty.owner != buyer, <Error<T>>::BuyerIsKittyOwner);
-			if let Some(ask_price) = kitty.price {
+			if let Some(ref price) = kitty.price {
+				let ask_price = Self::native_price(price)?;
 				ensure!(ask_price <= bid_price, <Error<T>>::KittyBidPriceTooLow);
 			} else {
 				Err(<Error<T>>::KittyNotForSale)?;
@@ -359,6 +425,26 @@ pub mod pallet {
 				None => Err(<Error<T>>::KittyNotExist)
 			}
 		}
+		// asking price in native currency, USD prices are converted with the oracle price
+		pub fn native_price(price: &KittyPrice<BalanceOf<T>>) -> Result<BalanceOf<T>, Error<T>> {
+			let usd = match price {
+				KittyPrice::Native(amount) => return Ok(*amount),
+				KittyPrice::Usd(usd) => usd,
+			};
+			let tokens = usd.checked_div(&Self::oracle_price()?).ok_or(<Error<T>>::PriceOverflow)?;
+			tokens
+				.checked_mul_int(T::NativeUnit::get().saturated_into::<u128>())
+				.and_then(|amount| amount.try_into().ok())
+				.ok_or(<Error<T>>::PriceOverflow)
+		}
+		// fresh USD price of one native token
+		fn oracle_price() -> Result<FixedU128, Error<T>> {
+			let (price, updated_at) = T::PriceOracle::usd_price().ok_or(<Error<T>>::PriceUnavailable)?;
+			ensure!(!price.is_zero(), <Error<T>>::PriceUnavailable);
+			let age = <frame_system::Pallet<T>>::block_number().saturating_sub(updated_at);
+			ensure!(age <= T::MaxPriceAge::get(), <Error<T>>::StalePrice);
+			Ok(price)
+		}
 		// transfer kitty to other
 		#[transactional]
 		pub fn transfer_kitty_to(
@@ -0,0 +1,41 @@
+// Storage migrations of the kitties pallet.
+
+use crate::{pallet::Kitties, Config, Gender, Kitty, KittyPrice, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{Currency, Get, StorageVersion},
+	weights::Weight,
+};
+
+// Version 0 listed kitties with a native balance only, version 1 with a KittyPrice.
+pub mod v1 {
+	use super::*;
+
+	// layout of a kitty in version 0
+	#[derive(Encode, Decode)]
+	pub struct OldKitty<AccountId, Balance> {
+		pub dna: [u8; 16],
+		pub price: Option<Balance>,
+		pub gender: Gender,
+		pub owner: AccountId,
+	}
+
+	// list the kitties for sale at their native price
+	pub fn migrate<T: Config>() -> Weight {
+		let mut kitties = 0u64;
+		Kitties::<T>::translate::<OldKitty<T::AccountId, <T::Currency as Currency<T::AccountId>>::Balance>, _>(
+			|_, old| {
+				kitties += 1;
+				Some(Kitty {
+					dna: old.dna,
+					price: old.price.map(KittyPrice::Native),
+					gender: old.gender,
+					owner: old.owner,
+				})
+			},
+		);
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(kitties, kitties + 1)
+	}
+}
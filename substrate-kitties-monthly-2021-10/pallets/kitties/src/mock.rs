@@ -5,7 +5,9 @@ use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
 	traits::{BlakeTwo256, IdentityLookup},
+	FixedU128,
 };
+use std::cell::RefCell;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -31,6 +33,8 @@ parameter_types! {
 	pub const MaxLocks: u32 = 50;
 	pub const MaxKittyOwned: u32 = 3;
 	pub const KittyReservationFee: u128 = 1000;
+	pub const MaxPriceAge: u64 = 10;
+	pub const NativeUnit: u128 = 1000;
 }
 
 impl system::Config for Test {
@@ -73,12 +77,32 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = ();
 }
 
+thread_local! {
+	static ORACLE_PRICE: RefCell<Option<(FixedU128, u64)>> = RefCell::new(None);
+}
+
+// Oracle returning the price set by `set_oracle_price`
+pub struct MockOracle;
+
+impl pallet_kitties::PriceOracle<u64> for MockOracle {
+	fn usd_price() -> Option<(FixedU128, u64)> {
+		ORACLE_PRICE.with(|price| *price.borrow())
+	}
+}
+
+pub fn set_oracle_price(price: Option<(FixedU128, u64)>) {
+	ORACLE_PRICE.with(|current| *current.borrow_mut() = price);
+}
+
 impl pallet_kitties::Config for Test {
 	type Event = Event;
 	type Randomness = RandomnessCollectiveFlip;
 	type Currency = Balances;
 	type MaxKittyOwned = MaxKittyOwned;
 	type ReservationFee = KittyReservationFee;
+	type PriceOracle = MockOracle;
+	type MaxPriceAge = MaxPriceAge;
+	type NativeUnit = NativeUnit;
 }
 
 // Build genesis storage according to the mock runtime.
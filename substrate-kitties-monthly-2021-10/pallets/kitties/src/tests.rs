@@ -1,5 +1,6 @@
-use crate::{mock::*, Error};
+use crate::{mock::*, Error, KittyPrice};
 use frame_support::{assert_noop, assert_ok};
+use sp_runtime::{FixedPointNumber, FixedU128};
 
 // create=========================================================
 #[test]
@@ -43,7 +44,7 @@ fn create_kitty_failed_when_sender_has_less_balance() {
 fn set_price_works() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(10))));
     });
 }
 #[test]
@@ -51,7 +52,7 @@ fn set_price_failed_when_sender_is_not_owner() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
         assert_noop!(
-            KittiesTest::set_price(Origin::signed(2), 0, Some(10)), 
+            KittiesTest::set_price(Origin::signed(2), 0, Some(KittyPrice::Native(10))), 
             Error::<Test>::NotKittyOwner
         );
     });
@@ -61,7 +62,7 @@ fn set_price_failed_when_invalid_kitty_id() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
         assert_noop!(
-            KittiesTest::set_price(Origin::signed(1), 1, Some(10)), 
+            KittiesTest::set_price(Origin::signed(1), 1, Some(KittyPrice::Native(10))), 
             Error::<Test>::KittyNotExist
         );
     });
@@ -123,7 +124,7 @@ fn transfer_failed_when_invalid_kitty_id() {
 fn buy_kitty_works() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(10))));
         assert_ok!(KittiesTest::buy_kitty(Origin::signed(2), 0, 11));
         assert_eq!(KittiesTest::owner(0), 2);
     });
@@ -132,7 +133,7 @@ fn buy_kitty_works() {
 fn buy_kitty_failed_when_invalid_kitty_id() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(10))));
         assert_noop!(
             KittiesTest::buy_kitty(Origin::signed(2), 1, 11), 
             Error::<Test>::KittyNotExist
@@ -143,7 +144,7 @@ fn buy_kitty_failed_when_invalid_kitty_id() {
 fn buy_kitty_failed_when_buyer_is_owner() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(10))));
         assert_noop!(
             KittiesTest::buy_kitty(Origin::signed(1), 0, 11), 
             Error::<Test>::BuyerIsKittyOwner
@@ -154,7 +155,7 @@ fn buy_kitty_failed_when_buyer_is_owner() {
 fn buy_kitty_failed_when_bid_price_too_low() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(10)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(10))));
         assert_noop!(
             KittiesTest::buy_kitty(Origin::signed(2), 0, 9), 
             Error::<Test>::KittyBidPriceTooLow
@@ -175,7 +176,7 @@ fn buy_kitty_failed_when_kitty_not_for_sale() {
 fn buy_kitty_failed_when_not_enough_balance() {
     new_test_ext().execute_with( || {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(100)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(100))));
         assert_noop!(
             KittiesTest::buy_kitty(Origin::signed(3), 0, 10000000), 
             Error::<Test>::NotEnoughBalance
@@ -189,13 +190,97 @@ fn buy_kitty_failed_when_buyer_kitty_over_limited() {
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
         assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
-        assert_ok!(KittiesTest::set_price(Origin::signed(2), 0, Some(10)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(2), 0, Some(KittyPrice::Native(10))));
         assert_noop!(
             KittiesTest::buy_kitty(Origin::signed(1), 0, 11), 
             Error::<Test>::ExceedMaxKittyOwned
         );
     });
 }
+// usd price=========================================================
+fn usd(n: u128) -> KittyPrice<u128> {
+    KittyPrice::Usd(FixedU128::saturating_from_integer(n))
+}
+#[test]
+fn buy_kitty_listed_in_usd_works() {
+    new_test_ext().execute_with( || {
+        // 2 USD per token of 1000 units
+        set_oracle_price(Some((FixedU128::saturating_from_integer(2), 1)));
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))));
+        assert_eq!(KittiesTest::native_price(&usd(10)).ok(), Some(5000));
+        assert_noop!(
+            KittiesTest::buy_kitty(Origin::signed(2), 0, 4999),
+            Error::<Test>::KittyBidPriceTooLow
+        );
+        let seller_balance = Balances::free_balance(1);
+        assert_ok!(KittiesTest::buy_kitty(Origin::signed(2), 0, 5000));
+        assert_eq!(KittiesTest::owner(0), 2);
+        assert_eq!(Balances::free_balance(1), seller_balance + 5000);
+    });
+}
+#[test]
+fn buy_kitty_listed_in_usd_follows_oracle_price() {
+    new_test_ext().execute_with( || {
+        set_oracle_price(Some((FixedU128::saturating_from_integer(2), 1)));
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))));
+        // the native price halves when the token price doubles
+        set_oracle_price(Some((FixedU128::saturating_from_integer(4), 1)));
+        assert_ok!(KittiesTest::buy_kitty(Origin::signed(2), 0, 2500));
+        // and is rounded down
+        set_oracle_price(Some((FixedU128::saturating_from_integer(3), 1)));
+        assert_eq!(KittiesTest::native_price(&usd(10)).ok(), Some(3333));
+    });
+}
+#[test]
+fn set_price_in_usd_failed_without_oracle_price() {
+    new_test_ext().execute_with( || {
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        assert_noop!(
+            KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))),
+            Error::<Test>::PriceUnavailable
+        );
+        set_oracle_price(Some((FixedU128::saturating_from_integer(0), 1)));
+        assert_noop!(
+            KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))),
+            Error::<Test>::PriceUnavailable
+        );
+    });
+}
+#[test]
+fn set_price_in_usd_failed_when_oracle_price_is_stale() {
+    new_test_ext().execute_with( || {
+        set_oracle_price(Some((FixedU128::saturating_from_integer(2), 1)));
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        // prices are fresh for 10 blocks
+        System::set_block_number(11);
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))));
+        System::set_block_number(12);
+        assert_noop!(
+            KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))),
+            Error::<Test>::StalePrice
+        );
+        // native prices do not need the oracle
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(KittyPrice::Native(10))));
+    });
+}
+#[test]
+fn buy_kitty_failed_when_oracle_price_is_stale() {
+    new_test_ext().execute_with( || {
+        set_oracle_price(Some((FixedU128::saturating_from_integer(2), 1)));
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        assert_ok!(KittiesTest::set_price(Origin::signed(1), 0, Some(usd(10))));
+        System::set_block_number(12);
+        assert_noop!(
+            KittiesTest::buy_kitty(Origin::signed(2), 0, 5000),
+            Error::<Test>::StalePrice
+        );
+        // until the oracle is updated
+        set_oracle_price(Some((FixedU128::saturating_from_integer(2), 12)));
+        assert_ok!(KittiesTest::buy_kitty(Origin::signed(2), 0, 5000));
+    });
+}
 // breed kitty===========================================================
 #[test]
 fn breed_kitty_works() {
@@ -227,3 +312,32 @@ fn breed_kitty_failed_when_kitties_is_out_of_limit() {
             Error::<Test>::ExceedMaxKittyOwned);
     });
 }
+// migration=========================================================
+#[test]
+fn migration_lists_kitties_at_native_price() {
+    use codec::Encode;
+    use frame_support::{
+        storage::migration::put_storage_value,
+        traits::{Hooks, StorageVersion},
+        StorageHasher, Twox64Concat,
+    };
+    use crate::migrations::v1::OldKitty;
+
+    new_test_ext().execute_with( || {
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        assert_ok!(KittiesTest::create_kitty(Origin::signed(1)));
+        // version 0 stored the price as a plain balance
+        StorageVersion::new(0).put::<KittiesTest>();
+        for (kitty_id, price) in [(0u64, Some(10u128)), (1, None)] {
+            let kitty = KittiesTest::kitties(kitty_id).unwrap();
+            let old = OldKitty { dna: kitty.dna, price, gender: kitty.gender, owner: kitty.owner };
+            put_storage_value(b"KittiesTest", b"Kitties", &Twox64Concat::hash(&kitty_id.encode()), old);
+        }
+
+        <KittiesTest as Hooks<u64>>::on_runtime_upgrade();
+        assert_eq!(StorageVersion::get::<KittiesTest>(), 1);
+        assert_eq!(KittiesTest::kitties(0).unwrap().price, Some(KittyPrice::Native(10)));
+        assert_eq!(KittiesTest::kitties(1).unwrap().price, None);
+        assert_eq!(KittiesTest::kitties(1).unwrap().owner, 1);
+    });
+}
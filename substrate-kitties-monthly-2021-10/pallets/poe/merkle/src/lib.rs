@@ -0,0 +1,84 @@
+// compile with std or no_std mode
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Merkle primitives for batch notarization in pallet_poe.
+// the verification half is no_std and used on chain, the tree builder
+// half is std only and used off chain to produce roots and proofs.
+
+use codec::{Decode, Encode};
+use scale_info::TypeInfo;
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+// std-side tree builder
+#[cfg(feature = "std")]
+mod tree;
+#[cfg(feature = "std")]
+pub use tree::MerkleTree;
+
+#[cfg(test)]
+mod tests;
+
+// 32 bytes blake2 hash, used for leaves, nodes and roots
+pub type Hash = [u8; 32];
+
+// domain separation prefixes, so a node hash can never be replayed as a leaf
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+// inclusion proof of one leaf: its position plus the sibling hashes from bottom to top
+#[derive(Clone, Encode, Decode, PartialEq, Eq, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MerkleProof {
+    pub leaf_index: u32,
+    pub siblings: Vec<Hash>,
+}
+
+// hash a leaf (the document hash) into the bottom level of the tree
+pub fn hash_leaf(leaf: &Hash) -> Hash {
+    let mut buf = [0u8; 33];
+    buf[0] = LEAF_PREFIX;
+    buf[1..].copy_from_slice(leaf);
+    blake2_256(&buf)
+}
+
+// hash two children into their parent
+pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    let mut buf = [0u8; 65];
+    buf[0] = NODE_PREFIX;
+    buf[1..33].copy_from_slice(left);
+    buf[33..].copy_from_slice(right);
+    blake2_256(&buf)
+}
+
+// recompute the root of a tree with `leaf_count` leaves from a leaf and its proof.
+// an unpaired last node of a level is promoted to the next level unchanged.
+// return None if the proof does not fit the tree shape
+pub fn compute_root(leaf: &Hash, proof: &MerkleProof, leaf_count: u32) -> Option<Hash> {
+    if proof.leaf_index >= leaf_count {
+        return None;
+    }
+    let mut siblings = proof.siblings.iter();
+    let mut hash = hash_leaf(leaf);
+    let mut index = proof.leaf_index;
+    let mut width = leaf_count;
+    while width > 1 {
+        if index % 2 == 1 {
+            hash = hash_node(siblings.next()?, &hash);
+        } else if index + 1 < width {
+            hash = hash_node(&hash, siblings.next()?);
+        }
+        index /= 2;
+        width = (width + 1) / 2;
+    }
+    // every sibling must be consumed
+    match siblings.next() {
+        Some(_) => None,
+        None => Some(hash),
+    }
+}
+
+// check that `leaf` is included in the tree committed to by `root`
+pub fn verify_proof(root: &Hash, leaf: &Hash, proof: &MerkleProof, leaf_count: u32) -> bool {
+    compute_root(leaf, proof, leaf_count).map_or(false, |computed| &computed == root)
+}
@@ -0,0 +1,68 @@
+use super::*;
+
+fn leaves(n: u8) -> Vec<Hash> {
+    (0..n).map(|i| blake2_256(&[i])).collect()
+}
+
+#[test]
+fn single_leaf_root_is_hashed_leaf() {
+    let tree = MerkleTree::from_leaves(leaves(1)).unwrap();
+    assert_eq!(tree.root(), hash_leaf(&leaves(1)[0]));
+    let proof = tree.proof(0).unwrap();
+    assert!(proof.siblings.is_empty());
+    assert!(verify_proof(&tree.root(), &leaves(1)[0], &proof, 1));
+}
+
+#[test]
+fn empty_set_builds_no_tree() {
+    assert!(MerkleTree::from_leaves(vec![]).is_none());
+}
+
+#[test]
+fn every_leaf_verifies_for_odd_and_even_sizes() {
+    for n in 1..=9 {
+        let docs = leaves(n);
+        let tree = MerkleTree::from_leaves(docs.clone()).unwrap();
+        for (i, doc) in docs.iter().enumerate() {
+            let proof = tree.proof(i as u32).unwrap();
+            assert!(verify_proof(&tree.root(), doc, &proof, n as u32), "leaf {} of {}", i, n);
+        }
+    }
+}
+
+#[test]
+fn proof_fails_for_wrong_leaf_or_count() {
+    let docs = leaves(5);
+    let tree = MerkleTree::from_leaves(docs.clone()).unwrap();
+    let proof = tree.proof(2).unwrap();
+    // wrong document
+    assert!(!verify_proof(&tree.root(), &docs[3], &proof, 5));
+    // wrong tree shape
+    assert!(!verify_proof(&tree.root(), &docs[2], &proof, 3));
+    // index out of range
+    let mut bad = proof.clone();
+    bad.leaf_index = 5;
+    assert!(!verify_proof(&tree.root(), &docs[2], &bad, 5));
+    // extra sibling
+    let mut bad = proof;
+    bad.siblings.push([0u8; 32]);
+    assert!(!verify_proof(&tree.root(), &docs[2], &bad, 5));
+}
+
+#[test]
+fn from_dir_hashes_files_in_path_order() {
+    let dir = std::env::temp_dir().join(format!("poe-merkle-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("b.txt"), b"b").unwrap();
+    std::fs::write(dir.join("a.txt"), b"a").unwrap();
+    std::fs::write(dir.join("sub").join("c.txt"), b"c").unwrap();
+
+    let tree = MerkleTree::from_dir(&dir).unwrap().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let expected = vec![blake2_256(b"a"), blake2_256(b"b"), blake2_256(b"c")];
+    assert_eq!(tree.leaves(), &expected[..]);
+    assert_eq!(tree.leaf_count(), 3);
+    let proof = tree.proof_of(&blake2_256(b"c")).unwrap();
+    assert!(verify_proof(&tree.root(), &blake2_256(b"c"), &proof, 3));
+}
@@ -0,0 +1,96 @@
+// std-side merkle tree builder, turns a set of documents into a root for
+// `create_batch_claim` and hands out proofs for `verify_inclusion`
+
+use crate::{hash_leaf, hash_node, Hash, MerkleProof};
+use sp_io::hashing::blake2_256;
+use std::{fs, io, path::Path};
+
+pub struct MerkleTree {
+    // levels[0] is the hashed leaves, the last level holds the root
+    levels: Vec<Vec<Hash>>,
+    // document hashes in leaf order
+    leaves: Vec<Hash>,
+}
+
+impl MerkleTree {
+    // build a tree from document hashes, return None for an empty set
+    pub fn from_leaves(leaves: Vec<Hash>) -> Option<Self> {
+        if leaves.is_empty() {
+            return None;
+        }
+        let mut levels = vec![leaves.iter().map(hash_leaf).collect::<Vec<_>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(left, right),
+                    // unpaired last node is promoted unchanged
+                    [single] => *single,
+                    _ => unreachable!("chunks(2) yields one or two items"),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Some(MerkleTree { levels, leaves })
+    }
+
+    // build a tree from every file under `dir`, recursing into sub directories.
+    // files are sorted by path so the same directory always gives the same root
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<Option<Self>> {
+        let mut paths = Vec::new();
+        collect_files(dir.as_ref(), &mut paths)?;
+        paths.sort();
+        let mut leaves = Vec::with_capacity(paths.len());
+        for path in &paths {
+            leaves.push(blake2_256(&fs::read(path)?));
+        }
+        Ok(Self::from_leaves(leaves))
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    pub fn leaf_count(&self) -> u32 {
+        self.leaves.len() as u32
+    }
+
+    pub fn leaves(&self) -> &[Hash] {
+        &self.leaves
+    }
+
+    // proof for the leaf at `index`, None if out of range
+    pub fn proof(&self, index: u32) -> Option<MerkleProof> {
+        if index as usize >= self.leaves.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut i = index as usize;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = i ^ 1;
+            if sibling < level.len() {
+                siblings.push(level[sibling]);
+            }
+            i /= 2;
+        }
+        Some(MerkleProof { leaf_index: index, siblings })
+    }
+
+    // proof for a document hash, None if it is not part of the tree
+    pub fn proof_of(&self, leaf: &Hash) -> Option<MerkleProof> {
+        let index = self.leaves.iter().position(|l| l == leaf)?;
+        self.proof(index as u32)
+    }
+}
+
+fn collect_files(dir: &Path, paths: &mut Vec<std::path::PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
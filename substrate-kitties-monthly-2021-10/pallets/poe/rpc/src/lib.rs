@@ -0,0 +1,117 @@
+//! RPC interface for the poe pallet.
+//! `poe_getReceipt` hands out a receipt of a claim which carries a storage read proof,
+//! so that an auditor can check it offline with [`verify_receipt`].
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_poe_runtime_api::PoeApi as PoeRuntimeApi;
+use sc_client_api::ProofProvider;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+mod receipt;
+pub use receipt::{proofs_storage_key, verify_receipt, ClaimReceipt, ReceiptError};
+
+#[cfg(test)]
+mod tests;
+
+/// Poe RPC methods.
+#[rpc]
+pub trait PoeApi<BlockHash, AccountId, BlockNumber> {
+	/// Get the receipt of `claim` at block `at` (finalized block if `None`, so that the proof
+	/// does not point at a block which can be reorged away).
+	/// Return `None` if the claim does not exist.
+	#[rpc(name = "poe_getReceipt")]
+	fn get_receipt(
+		&self,
+		claim: Bytes,
+		at: Option<BlockHash>,
+	) -> Result<Option<ClaimReceipt<AccountId, BlockNumber, BlockHash>>>;
+}
+
+/// Implementation of the poe RPC methods.
+pub struct Poe<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Poe<C, B> {
+	/// Create new `Poe` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error codes of the poe RPC methods.
+pub enum Error {
+	/// The call to the runtime failed.
+	RuntimeError,
+	/// The storage read proof could not be generated.
+	ProofError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+			Error::ProofError => 2,
+		}
+	}
+}
+
+fn rpc_error(code: Error, message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(code.into()),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber> PoeApi<<Block as BlockT>::Hash, AccountId, BlockNumber>
+	for Poe<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + ProofProvider<Block>,
+	C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec,
+	BlockNumber: Codec,
+{
+	fn get_receipt(
+		&self,
+		claim: Bytes,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<ClaimReceipt<AccountId, BlockNumber, <Block as BlockT>::Hash>>> {
+		let api = self.client.runtime_api();
+		let block_hash = at.unwrap_or_else(|| self.client.info().finalized_hash);
+		let at = BlockId::hash(block_hash);
+
+		let (owner, block_number) = match api
+			.claim(&at, claim.to_vec())
+			.map_err(|e| rpc_error(Error::RuntimeError, "Unable to query claim.", e))?
+		{
+			Some(entry) => entry,
+			None => return Ok(None),
+		};
+		let key = api
+			.proofs_key(&at, claim.to_vec())
+			.map_err(|e| rpc_error(Error::RuntimeError, "Unable to query storage key.", e))?;
+		let proof = self
+			.client
+			.read_proof(&at, &mut std::iter::once(key.as_slice()))
+			.map_err(|e| rpc_error(Error::ProofError, "Unable to generate read proof.", e))?;
+
+		Ok(Some(ClaimReceipt {
+			claim,
+			owner,
+			block_number,
+			block_hash,
+			proof: proof.iter_nodes().map(Into::into).collect(),
+		}))
+	}
+}
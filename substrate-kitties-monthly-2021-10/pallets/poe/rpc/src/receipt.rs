@@ -0,0 +1,79 @@
+//! Claim receipts and their offline verification.
+
+use codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use sp_core::{
+	hashing::{blake2_128, twox_128},
+	Bytes, H256,
+};
+use sp_runtime::traits::BlakeTwo256;
+use sp_state_machine::{read_proof_check, StorageProof};
+
+/// Proof that a claim was stored in `Proofs` at a given block.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaimReceipt<AccountId, BlockNumber, BlockHash> {
+	/// The claim itself.
+	pub claim: Bytes,
+	/// Owner of the claim.
+	pub owner: AccountId,
+	/// Block number in which the claim was created or last transferred.
+	pub block_number: BlockNumber,
+	/// Hash of the block whose state the proof was read from.
+	pub block_hash: BlockHash,
+	/// Trie nodes proving the `Proofs` entry of the claim.
+	pub proof: Vec<Bytes>,
+}
+
+/// Reasons a receipt is rejected.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ReceiptError {
+	/// The proof does not belong to the given state root.
+	InvalidProof,
+	/// The proof shows the claim does not exist.
+	ClaimNotExist,
+	/// The stored entry could not be decoded.
+	InvalidValue,
+	/// The stored owner or block number differs from the receipt.
+	Mismatch,
+}
+
+/// Storage key of the `Proofs` entry of `claim`, for the poe pallet named `pallet_name` in
+/// `construct_runtime!`.
+pub fn proofs_storage_key(pallet_name: &str, claim: &[u8]) -> Vec<u8> {
+	let encoded = claim.encode();
+	let mut key = twox_128(pallet_name.as_bytes()).to_vec();
+	key.extend_from_slice(&twox_128(b"Proofs"));
+	// Blake2_128Concat hasher
+	key.extend_from_slice(&blake2_128(&encoded));
+	key.extend_from_slice(&encoded);
+	key
+}
+
+/// Verify `receipt` against the state root of a block the caller already trusts, e.g. the
+/// header of a finalized block. The storage key is derived from the claim rather than taken
+/// from the receipt, so a receipt cannot prove a different claim.
+pub fn verify_receipt<AccountId, BlockNumber, BlockHash>(
+	receipt: &ClaimReceipt<AccountId, BlockNumber, BlockHash>,
+	pallet_name: &str,
+	state_root: H256,
+) -> Result<(), ReceiptError>
+where
+	AccountId: Decode + PartialEq,
+	BlockNumber: Decode + PartialEq,
+{
+	let key = proofs_storage_key(pallet_name, &receipt.claim);
+	let proof = StorageProof::new(receipt.proof.iter().map(|node| node.to_vec()).collect());
+	let mut values = read_proof_check::<BlakeTwo256, _>(state_root, proof, std::iter::once(&key))
+		.map_err(|_| ReceiptError::InvalidProof)?;
+	let value = values
+		.remove(&key)
+		.ok_or(ReceiptError::InvalidProof)?
+		.ok_or(ReceiptError::ClaimNotExist)?;
+	let (owner, block_number) = <(AccountId, BlockNumber)>::decode(&mut &value[..])
+		.map_err(|_| ReceiptError::InvalidValue)?;
+	if owner != receipt.owner || block_number != receipt.block_number {
+		return Err(ReceiptError::Mismatch)
+	}
+	Ok(())
+}
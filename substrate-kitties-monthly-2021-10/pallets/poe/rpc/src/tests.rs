@@ -0,0 +1,57 @@
+use super::*;
+use codec::Encode;
+use sp_core::H256;
+use sp_runtime::traits::BlakeTwo256;
+use sp_state_machine::{prove_read, InMemoryBackend};
+
+const PALLET: &str = "PoeModule";
+
+// state with one claim owned by account 1 at block 5, and the receipt of it
+fn receipt_with_root() -> (ClaimReceipt<u64, u32, H256>, H256) {
+	let claim = vec![0u8, 1];
+	let key = proofs_storage_key(PALLET, &claim);
+	let backend = InMemoryBackend::<BlakeTwo256>::from(vec![(
+		None,
+		vec![(key.clone(), Some((1u64, 5u32).encode()))],
+	)]);
+	let root = *backend.root();
+	let proof = prove_read(backend, std::iter::once(&key)).unwrap();
+	let receipt = ClaimReceipt {
+		claim: claim.into(),
+		owner: 1,
+		block_number: 5,
+		block_hash: H256::repeat_byte(1),
+		proof: proof.iter_nodes().map(Into::into).collect(),
+	};
+	(receipt, root)
+}
+
+#[test]
+fn verify_receipt_works() {
+	let (receipt, root) = receipt_with_root();
+	assert_eq!(verify_receipt(&receipt, PALLET, root), Ok(()));
+}
+
+#[test]
+fn verify_receipt_failed_when_root_is_wrong() {
+	let (receipt, _) = receipt_with_root();
+	assert_eq!(
+		verify_receipt(&receipt, PALLET, H256::repeat_byte(9)),
+		Err(ReceiptError::InvalidProof)
+	);
+}
+
+#[test]
+fn verify_receipt_failed_when_owner_is_forged() {
+	let (mut receipt, root) = receipt_with_root();
+	receipt.owner = 2;
+	assert_eq!(verify_receipt(&receipt, PALLET, root), Err(ReceiptError::Mismatch));
+}
+
+#[test]
+fn verify_receipt_failed_when_claim_is_swapped() {
+	let (mut receipt, root) = receipt_with_root();
+	// the proof does not cover the key of another claim
+	receipt.claim = vec![9u8, 9].into();
+	assert!(verify_receipt(&receipt, PALLET, root).is_err());
+}
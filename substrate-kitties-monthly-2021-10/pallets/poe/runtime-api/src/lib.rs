@@ -0,0 +1,23 @@
+// compile with std or no_std mode
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Runtime API of pallet_poe, called by the node to query claims without a transaction
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use poe_merkle::{Hash, MerkleProof};
+
+sp_api::decl_runtime_apis! {
+    pub trait PoeApi<AccountId, BlockNumber> where
+        AccountId: Codec,
+        BlockNumber: Codec,
+    {
+        // check a merkle proof of `leaf` against a root stored by `create_batch_claim`
+        fn verify_inclusion(root: Hash, leaf: Hash, proof: MerkleProof) -> bool;
+        // owner and creation block of a single claim
+        fn claim(claim: Vec<u8>) -> Option<(AccountId, BlockNumber)>;
+        // final storage key of the `Proofs` entry of a claim, used for read proofs
+        fn proofs_key(claim: Vec<u8>) -> Vec<u8>;
+    }
+}
@@ -23,6 +23,11 @@ pub mod pallet {
     use frame_system::pallet_prelude::*;
     // new add dependency
     use sp_std::vec::Vec;
+    // merkle root/leaf type and proof for batch claims
+    use poe_merkle::{Hash, MerkleProof};
+
+    // id of a claim namespace, assigned in creation order
+    pub type NamespaceId = u32;
 
     // pallet module configure interface
     // inherit from frame_system::Config
@@ -33,6 +38,9 @@ pub mod pallet {
         // implement with method get to get constant
         #[pallet::constant]
         type MaxVecLen: Get<u32>;
+        // max number of namespaced claims removed by one revoke_namespace call
+        #[pallet::constant]
+        type MaxRevokePerCall: Get<u32>;
     }
 
     // pallet::pallet macro define
@@ -56,6 +64,56 @@ pub mod pallet {
         (T::AccountId, T::BlockNumber)
     >;
 
+    // batch claims: merkle root -> (owner, block number, number of leaves)
+    #[pallet::storage]
+    #[pallet::getter(fn batch_proofs)]
+    pub type BatchProofs<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        Hash,
+        (T::AccountId, T::BlockNumber, u32)
+    >;
+
+    // next namespace id to assign
+    #[pallet::storage]
+    #[pallet::getter(fn next_namespace_id)]
+    pub type NextNamespaceId<T: Config> = StorageValue<_, NamespaceId, ValueQuery>;
+
+    // namespace id -> owner
+    #[pallet::storage]
+    #[pallet::getter(fn namespaces)]
+    pub type Namespaces<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        NamespaceId,
+        T::AccountId
+    >;
+
+    // (namespace id, account) -> () for accounts the owner granted admin role
+    #[pallet::storage]
+    #[pallet::getter(fn namespace_admins)]
+    pub type NamespaceAdmins<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        NamespaceId,
+        Blake2_128Concat,
+        T::AccountId,
+        ()
+    >;
+
+    // (namespace id, claim) -> (creator, block number)
+    // keyed by namespace first so a whole namespace can be removed by prefix
+    #[pallet::storage]
+    #[pallet::getter(fn namespaced_proofs)]
+    pub type NamespacedProofs<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        NamespaceId,
+        Blake2_128Concat,
+        Vec<u8>,
+        (T::AccountId, T::BlockNumber)
+    >;
+
     // define event enum type
     #[pallet::event]
 	/* out of version
@@ -67,7 +125,22 @@ pub mod pallet {
     pub enum Event<T: Config> {
         ClaimCreated(T::AccountId, Vec<u8>),
         ClaimRevoked(T::AccountId, Vec<u8>),
-        ClaimTransaction(T::AccountId, Vec<u8>, T::AccountId)
+        ClaimTransaction(T::AccountId, Vec<u8>, T::AccountId),
+        // \[owner, merkle_root, leaf_count\]
+        BatchClaimCreated(T::AccountId, Hash, u32),
+        // \[owner, namespace_id\]
+        NamespaceCreated(T::AccountId, NamespaceId),
+        // \[owner, namespace_id, admin\]
+        NamespaceAdminAdded(T::AccountId, NamespaceId, T::AccountId),
+        // \[owner, namespace_id, admin\]
+        NamespaceAdminRemoved(T::AccountId, NamespaceId, T::AccountId),
+        // \[sender, namespace_id, claim\]
+        NamespacedClaimCreated(T::AccountId, NamespaceId, Vec<u8>),
+        // \[sender, namespace_id, claim\]
+        NamespacedClaimRevoked(T::AccountId, NamespaceId, Vec<u8>),
+        // \[sender, namespace_id, removed, completed\]
+        // completed is false when claims remain and revoke_namespace has to be called again
+        NamespaceRevoked(T::AccountId, NamespaceId, u32, bool)
     }
 
     // define error enum type
@@ -77,7 +150,15 @@ pub mod pallet {
         ClaimNotExist,
         NotClaimOwner,
         NotDestination,
-        ClaimSizeOutOfBound
+        ClaimSizeOutOfBound,
+        BatchAlreadyExist,
+        // a batch must hold at least one leaf
+        EmptyBatch,
+        NamespaceIdOverflow,
+        NamespaceNotExist,
+        NotNamespaceOwner,
+        // sender is neither owner nor admin of the namespace
+        NotNamespaceAdmin
     }
 
     // functions active in specific period
@@ -164,5 +245,165 @@ pub mod pallet {
             // result
             Ok(().into())
         }
+
+        #[pallet::weight(0)]
+        // notarize a whole set of documents with one merkle root
+        pub fn create_batch_claim(
+            origin: OriginFor<T>,
+            merkle_root: Hash,
+            leaf_count: u32
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            ensure!(leaf_count > 0, Error::<T>::EmptyBatch);
+            ensure!(!BatchProofs::<T>::contains_key(&merkle_root), Error::<T>::BatchAlreadyExist);
+            BatchProofs::<T>::insert(
+                &merkle_root,
+                (sender.clone(), frame_system::Pallet::<T>::block_number(), leaf_count)
+            );
+            Self::deposit_event(Event::BatchClaimCreated(sender, merkle_root, leaf_count));
+            Ok(().into())
+        }
+
+        #[pallet::weight(0)]
+        // create a namespace owned by the sender
+        pub fn create_namespace(origin: OriginFor<T>) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            let namespace_id = Self::next_namespace_id();
+            let next_id = namespace_id.checked_add(1).ok_or(Error::<T>::NamespaceIdOverflow)?;
+            Namespaces::<T>::insert(namespace_id, &sender);
+            NextNamespaceId::<T>::put(next_id);
+            Self::deposit_event(Event::NamespaceCreated(sender, namespace_id));
+            Ok(().into())
+        }
+
+        #[pallet::weight(0)]
+        // grant admin role of a namespace, owner only
+        pub fn add_namespace_admin(
+            origin: OriginFor<T>,
+            namespace_id: NamespaceId,
+            admin: T::AccountId
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_namespace_owner(namespace_id, &sender)?;
+            NamespaceAdmins::<T>::insert(namespace_id, &admin, ());
+            Self::deposit_event(Event::NamespaceAdminAdded(sender, namespace_id, admin));
+            Ok(().into())
+        }
+
+        #[pallet::weight(0)]
+        // take back admin role of a namespace, owner only
+        pub fn remove_namespace_admin(
+            origin: OriginFor<T>,
+            namespace_id: NamespaceId,
+            admin: T::AccountId
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_namespace_owner(namespace_id, &sender)?;
+            NamespaceAdmins::<T>::remove(namespace_id, &admin);
+            Self::deposit_event(Event::NamespaceAdminRemoved(sender, namespace_id, admin));
+            Ok(().into())
+        }
+
+        #[pallet::weight(0)]
+        // create a claim under a namespace, owner or admin
+        pub fn create_namespaced_claim(
+            origin: OriginFor<T>,
+            namespace_id: NamespaceId,
+            claim: Vec<u8>
+        ) -> DispatchResultWithPostInfo {
+            ensure!((claim.len() as u32) <= T::MaxVecLen::get(), Error::<T>::ClaimSizeOutOfBound);
+            let sender = ensure_signed(origin)?;
+            Self::ensure_namespace_admin(namespace_id, &sender)?;
+            ensure!(
+                !NamespacedProofs::<T>::contains_key(namespace_id, &claim),
+                Error::<T>::ProofAlreadyExist
+            );
+            NamespacedProofs::<T>::insert(
+                namespace_id,
+                &claim,
+                (sender.clone(), frame_system::Pallet::<T>::block_number())
+            );
+            Self::deposit_event(Event::NamespacedClaimCreated(sender, namespace_id, claim));
+            Ok(().into())
+        }
+
+        #[pallet::weight(0)]
+        // revoke a single claim of a namespace, owner or admin
+        pub fn revoke_namespaced_claim(
+            origin: OriginFor<T>,
+            namespace_id: NamespaceId,
+            claim: Vec<u8>
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_namespace_admin(namespace_id, &sender)?;
+            ensure!(
+                NamespacedProofs::<T>::contains_key(namespace_id, &claim),
+                Error::<T>::ClaimNotExist
+            );
+            NamespacedProofs::<T>::remove(namespace_id, &claim);
+            Self::deposit_event(Event::NamespacedClaimRevoked(sender, namespace_id, claim));
+            Ok(().into())
+        }
+
+        // every removed claim is one read and one write, plus the read telling whether claims remain
+        #[pallet::weight({
+            let limit = (*limit).min(T::MaxRevokePerCall::get()) as u64;
+            T::DbWeight::get().reads_writes(limit + 1, limit)
+        })]
+        // remove up to `limit` claims of a namespace, owner or admin.
+        // call again while the event reports the namespace is not completed, also in the same block
+        pub fn revoke_namespace(
+            origin: OriginFor<T>,
+            namespace_id: NamespaceId,
+            limit: u32
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_namespace_admin(namespace_id, &sender)?;
+            let limit = limit.min(T::MaxRevokePerCall::get());
+            // iterate the keys, a limited remove_prefix would count the keys already removed
+            // in this block against the limit and remove nothing on a second call
+            let claims: Vec<Vec<u8>> =
+                NamespacedProofs::<T>::iter_key_prefix(namespace_id).take(limit as usize).collect();
+            for claim in claims.iter() {
+                NamespacedProofs::<T>::remove(namespace_id, claim);
+            }
+            let removed = claims.len() as u32;
+            let completed = NamespacedProofs::<T>::iter_key_prefix(namespace_id).next().is_none();
+            Self::deposit_event(Event::NamespaceRevoked(sender, namespace_id, removed, completed));
+            Ok(().into())
+        }
+    }
+
+    // inner function, not called by extrinsic
+    impl<T: Config> Pallet<T> {
+        // check a document hash against a stored batch root, used by the runtime api
+        pub fn verify_inclusion(root: Hash, leaf: Hash, proof: MerkleProof) -> bool {
+            match BatchProofs::<T>::get(&root) {
+                Some((_, _, leaf_count)) => poe_merkle::verify_proof(&root, &leaf, &proof, leaf_count),
+                None => false,
+            }
+        }
+
+        // sender must own the namespace
+        fn ensure_namespace_owner(namespace_id: NamespaceId, who: &T::AccountId) -> DispatchResult {
+            let owner = Namespaces::<T>::get(namespace_id).ok_or(Error::<T>::NamespaceNotExist)?;
+            ensure!(&owner == who, Error::<T>::NotNamespaceOwner);
+            Ok(())
+        }
+
+        // sender must own the namespace or hold its admin role
+        fn ensure_namespace_admin(namespace_id: NamespaceId, who: &T::AccountId) -> DispatchResult {
+            let owner = Namespaces::<T>::get(namespace_id).ok_or(Error::<T>::NamespaceNotExist)?;
+            ensure!(
+                &owner == who || NamespaceAdmins::<T>::contains_key(namespace_id, who),
+                Error::<T>::NotNamespaceAdmin
+            );
+            Ok(())
+        }
+
+        // storage key of a claim in `Proofs`, used by the node to build read proofs
+        pub fn proofs_key(claim: Vec<u8>) -> Vec<u8> {
+            Proofs::<T>::hashed_key_for(&claim)
+        }
     }
 }
@@ -57,6 +57,8 @@ impl pallet_poe::Config for Test {
 	type Event = Event;
 	// use ConstU32 as constant implement, set 2 as max input vec len
 	type MaxVecLen = ConstU32<2>;
+	// remove at most 2 namespaced claims per revoke_namespace call
+	type MaxRevokePerCall = ConstU32<2>;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -127,3 +127,166 @@ fn transfer_claim_failed_when_sender_is_dest() {
         );
     })
 }
+
+#[test]
+fn create_batch_claim_works() {
+    new_test_ext().execute_with(||{
+        let docs = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let tree = poe_merkle::MerkleTree::from_leaves(docs).unwrap();
+        assert_ok!(PoeModule::create_batch_claim(Origin::signed(1), tree.root(), tree.leaf_count()));
+        // assert that the root is stored with owner, block number and leaf count
+        assert_eq!(
+            BatchProofs::<Test>::get(&tree.root()),
+            Some((1, frame_system::Pallet::<Test>::block_number(), 3))
+        );
+    })
+}
+
+#[test]
+fn create_batch_claim_failed_when_batch_already_exist() {
+    new_test_ext().execute_with(||{
+        let root = [7u8; 32];
+        let _ = PoeModule::create_batch_claim(Origin::signed(1), root, 4);
+        assert_noop!(
+            PoeModule::create_batch_claim(Origin::signed(2), root, 4),
+            Error::<Test>::BatchAlreadyExist
+        );
+    })
+}
+
+#[test]
+fn create_batch_claim_failed_when_batch_is_empty() {
+    new_test_ext().execute_with(||{
+        assert_noop!(
+            PoeModule::create_batch_claim(Origin::signed(1), [7u8; 32], 0),
+            Error::<Test>::EmptyBatch
+        );
+    })
+}
+
+#[test]
+fn verify_inclusion_works() {
+    new_test_ext().execute_with(||{
+        let docs = vec![[1u8; 32], [2u8; 32], [3u8; 32], [4u8; 32], [5u8; 32]];
+        let tree = poe_merkle::MerkleTree::from_leaves(docs.clone()).unwrap();
+        let proof = tree.proof(3).unwrap();
+        // root not stored yet, nothing can be verified
+        assert!(!PoeModule::verify_inclusion(tree.root(), docs[3], proof.clone()));
+        let _ = PoeModule::create_batch_claim(Origin::signed(1), tree.root(), tree.leaf_count());
+        assert!(PoeModule::verify_inclusion(tree.root(), docs[3], proof.clone()));
+        // proof of a document which is not in the batch
+        assert!(!PoeModule::verify_inclusion(tree.root(), [9u8; 32], proof));
+    })
+}
+
+#[test]
+fn create_namespace_works() {
+    new_test_ext().execute_with(||{
+        assert_ok!(PoeModule::create_namespace(Origin::signed(1)));
+        assert_ok!(PoeModule::create_namespace(Origin::signed(2)));
+        // ids are assigned in creation order
+        assert_eq!(Namespaces::<Test>::get(0), Some(1));
+        assert_eq!(Namespaces::<Test>::get(1), Some(2));
+        assert_eq!(NextNamespaceId::<Test>::get(), 2);
+    })
+}
+
+#[test]
+fn namespace_admin_works() {
+    new_test_ext().execute_with(||{
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        // account 2 has no role yet
+        assert_noop!(
+            PoeModule::create_namespaced_claim(Origin::signed(2), 0, claim.clone()),
+            Error::<Test>::NotNamespaceAdmin
+        );
+        assert_ok!(PoeModule::add_namespace_admin(Origin::signed(1), 0, 2));
+        assert_ok!(PoeModule::create_namespaced_claim(Origin::signed(2), 0, claim.clone()));
+        assert_eq!(
+            NamespacedProofs::<Test>::get(0, &claim),
+            Some((2, frame_system::Pallet::<Test>::block_number()))
+        );
+        assert_ok!(PoeModule::remove_namespace_admin(Origin::signed(1), 0, 2));
+        assert_noop!(
+            PoeModule::revoke_namespaced_claim(Origin::signed(2), 0, claim.clone()),
+            Error::<Test>::NotNamespaceAdmin
+        );
+    })
+}
+
+#[test]
+fn add_namespace_admin_failed_when_sender_is_not_owner() {
+    new_test_ext().execute_with(||{
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        let _ = PoeModule::add_namespace_admin(Origin::signed(1), 0, 2);
+        // admins cannot grant roles
+        assert_noop!(
+            PoeModule::add_namespace_admin(Origin::signed(2), 0, 3),
+            Error::<Test>::NotNamespaceOwner
+        );
+        assert_noop!(
+            PoeModule::add_namespace_admin(Origin::signed(1), 1, 3),
+            Error::<Test>::NamespaceNotExist
+        );
+    })
+}
+
+#[test]
+fn create_namespaced_claim_failed_when_claim_already_exist() {
+    new_test_ext().execute_with(||{
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        let _ = PoeModule::create_namespaced_claim(Origin::signed(1), 0, claim.clone());
+        assert_noop!(
+            PoeModule::create_namespaced_claim(Origin::signed(1), 0, claim.clone()),
+            Error::<Test>::ProofAlreadyExist
+        );
+        // same claim in another namespace is fine
+        assert_ok!(PoeModule::create_namespaced_claim(Origin::signed(1), 1, claim.clone()));
+    })
+}
+
+#[test]
+fn revoke_namespaced_claim_works() {
+    new_test_ext().execute_with(||{
+        let claim = vec![0, 1];
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        assert_noop!(
+            PoeModule::revoke_namespaced_claim(Origin::signed(1), 0, claim.clone()),
+            Error::<Test>::ClaimNotExist
+        );
+        let _ = PoeModule::create_namespaced_claim(Origin::signed(1), 0, claim.clone());
+        assert_ok!(PoeModule::revoke_namespaced_claim(Origin::signed(1), 0, claim.clone()));
+        assert_eq!(NamespacedProofs::<Test>::get(0, &claim), None);
+    })
+}
+
+#[test]
+fn revoke_namespace_works_with_continuation() {
+    let mut ext = new_test_ext();
+    ext.execute_with(||{
+        System::set_block_number(1);
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        let _ = PoeModule::create_namespace(Origin::signed(1));
+        for claim in [vec![1], vec![2], vec![3]] {
+            let _ = PoeModule::create_namespaced_claim(Origin::signed(1), 0, claim);
+        }
+        let _ = PoeModule::create_namespaced_claim(Origin::signed(1), 1, vec![1]);
+    });
+    ext.commit_all().unwrap();
+    ext.execute_with(||{
+        // limit is capped by MaxRevokePerCall(2), so one claim remains
+        assert_ok!(PoeModule::revoke_namespace(Origin::signed(1), 0, 10));
+        System::assert_last_event(Event::PoeModule(crate::Event::NamespaceRevoked(1, 0, 2, false)));
+        assert_eq!(NamespacedProofs::<Test>::iter_prefix(0).count(), 1);
+
+        // continue in the same block until the namespace is empty
+        assert_ok!(PoeModule::revoke_namespace(Origin::signed(1), 0, 10));
+        System::assert_last_event(Event::PoeModule(crate::Event::NamespaceRevoked(1, 0, 1, true)));
+        assert_eq!(NamespacedProofs::<Test>::iter_prefix(0).count(), 0);
+        // other namespaces are untouched
+        assert_eq!(NamespacedProofs::<Test>::iter_prefix(1).count(), 1);
+    })
+}
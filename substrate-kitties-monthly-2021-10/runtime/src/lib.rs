@@ -46,6 +46,9 @@ pub use pallet_template;
 // Import the kitties pallet.
 pub use pallet_kitties;
 
+// Import the poe pallet.
+pub use pallet_poe;
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -144,6 +147,13 @@ parameter_types! {
 	// One can own at most 9,999 Kitties
 	pub const MaxKittyOwned: u32 = 3;
 	pub const KittyReservationFee: u128 = 1000;
+	// USD listings need an oracle price of the last 10 minutes
+	pub const KittyMaxPriceAge: BlockNumber = 10 * MINUTES;
+	pub const KittyNativeUnit: u128 = 1_000_000_000_000;
+	// max length of a single poe claim
+	pub const PoeMaxVecLen: u32 = 256;
+	// max number of namespaced claims removed by one revoke_namespace call
+	pub const PoeMaxRevokePerCall: u32 = 500;
 }
 
 // Configure FRAME pallets to include in runtime.
@@ -290,6 +300,17 @@ impl pallet_kitties::Config for Runtime {
 	type Currency = Balances;
 	type MaxKittyOwned = MaxKittyOwned;
 	type ReservationFee = KittyReservationFee;
+	// no price feed in this runtime yet, so kitties are only listed in native currency
+	type PriceOracle = ();
+	type MaxPriceAge = KittyMaxPriceAge;
+	type NativeUnit = KittyNativeUnit;
+}
+
+/// Configure the pallet-poe in pallets/poe.
+impl pallet_poe::Config for Runtime {
+	type Event = Event;
+	type MaxVecLen = PoeMaxVecLen;
+	type MaxRevokePerCall = PoeMaxRevokePerCall;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -311,6 +332,8 @@ construct_runtime!(
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
 		// Include the custom logic from the pallet-kitties in the runtime.
 		Kitties: pallet_kitties::{Pallet, Call, Config<T>, Storage, Event<T>},
+		// Include the custom logic from the pallet-poe in the runtime.
+		PoeModule: pallet_poe::{Pallet, Call, Storage, Event<T>},
 	}
 );
 
@@ -472,6 +495,24 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_poe_runtime_api::PoeApi<Block, AccountId, BlockNumber> for Runtime {
+		fn verify_inclusion(
+			root: pallet_poe_runtime_api::Hash,
+			leaf: pallet_poe_runtime_api::Hash,
+			proof: pallet_poe_runtime_api::MerkleProof,
+		) -> bool {
+			PoeModule::verify_inclusion(root, leaf, proof)
+		}
+
+		fn claim(claim: Vec<u8>) -> Option<(AccountId, BlockNumber)> {
+			PoeModule::proofs(claim)
+		}
+
+		fn proofs_key(claim: Vec<u8>) -> Vec<u8> {
+			PoeModule::proofs_key(claim)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (